@@ -1,5 +1,18 @@
-foreign_resource('integration_tests', [rust_main]).
+% Run the tests with `cargo build && sicstus -l integration_tests --goal "rust_main, halt."`.
+% The foreign declarations of the #[sicstus_rs::foreign] functions are generated by build.rs.
+:- include('target/debug/integration_tests_foreign').
 
-foreign(rust_main, c, rust_main).
+:- load_foreign_resource('target/debug/integration_tests').
 
-:- load_foreign_resource(integration_tests).
+test_fact(1).
+test_fact(2).
+test_fact(3).
+
+test_throw(instantiation) :- atom_length(_, _).
+test_throw(type) :- throw(error(type_error(integer, foo), context(test_throw/1, _))).
+test_throw(custom) :- throw(my_exception).
+
+test_shapes([circle(1.5), rectangle(point(0, 0), 2.0, 3.0), empty]).
+test_person(person('Ada', [0'L, 0'o, 0'v, 0'e, 0'l, 0'a, 0'c, 0'e], 36)).
+test_shared(f(X, Y)) :- X == Y.
+test_succ(X, Y) :- Y is X + 1.
@@ -0,0 +1,66 @@
+use std::cmp::Ordering;
+
+use sicstus_rs::{list, term, Atom, Compound, Error, TermRef, TermScope};
+
+use crate::helpers::assert_same;
+
+pub fn test_compound() {
+    test_compound_builder();
+    test_term_macro();
+    test_term_macro_variables();
+}
+
+fn test_compound_builder() {
+    TermScope::run(|scope| {
+        let term = Compound::new("foo")
+            .arg(Atom::from("bar"))
+            .arg(42)
+            .arg(list![1, "a", 2.5])
+            .build(scope)
+            .unwrap();
+        assert_same(&term, "foo(bar, 42, [1, a, 2.5])");
+
+        let nested = Compound::new("f")
+            .arg(Compound::new("g").arg(1))
+            .arg(Compound::new("h"))
+            .build(scope)
+            .unwrap();
+        assert_same(&nested, "f(g(1), h)");
+        assert!(matches!(
+            Compound::new("f").arg("a\0b").build(scope),
+            Err(Error::InteriorNul(_))
+        ));
+    });
+    sicstus_rs::println!("test_compound_builder, Ok");
+}
+
+fn test_term_macro() {
+    TermScope::run(|scope| {
+        let x = 3;
+        let name = String::from("Ada");
+        let term = term!(scope; point({ x }, { name }, -2, "hello world", [a, [], f(true)])).unwrap();
+        assert_same(
+            &term,
+            "point(3, 'Ada', -2, 'hello world', [a, [], f(true)])",
+        );
+        assert_same(&term!(scope; nil).unwrap(), "nil");
+        assert_same(&term!(scope; []).unwrap(), "[]");
+        assert_same(&term!(scope; "with space"(1)).unwrap(), "'with space'(1)");
+        assert_same(&term!(scope; { (1, 2) }).unwrap(), "1-2");
+    });
+    sicstus_rs::println!("test_term_macro, Ok");
+}
+
+fn test_term_macro_variables() {
+    TermScope::run(|scope| {
+        let term = term!(scope; f(X, Y, X, _, _, [H | T])).unwrap();
+        let args: Vec<TermRef> = (1..=6).map(|i| term.get_arg(i).unwrap()).collect();
+        assert!(args[0].is_variable());
+        assert_eq!(args[0].cmp(&args[2]), Ordering::Equal);
+        assert_ne!(args[0].cmp(&args[1]), Ordering::Equal);
+        assert_ne!(args[3].cmp(&args[4]), Ordering::Equal);
+        let (head, tail) = args[5].get_list().unwrap();
+        assert!(head.is_variable() && tail.is_variable());
+    });
+    sicstus_rs::println!("test_term_macro_variables, Ok");
+}
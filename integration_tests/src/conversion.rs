@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use sicstus_rs::{Error, TermRef, TermScope, ToTerm};
+
+use crate::helpers::assert_same;
+
+pub fn test_conversion() {
+    test_integer_from();
+    test_text_and_bool();
+    test_containers();
+    test_tuples();
+    test_btree_map();
+    test_conversion_errors();
+}
+
+fn test_integer_from() {
+    TermScope::run(|scope| {
+        let term = u64::MAX.to_term(scope).unwrap();
+        assert_eq!(u64::try_from(&term).unwrap(), u64::MAX);
+        assert!(i64::try_from(&term).is_err());
+        assert_eq!(i8::try_from(&(-128i8).to_term(scope).unwrap()).unwrap(), -128);
+        assert_eq!(u16::try_from(&65535u16.to_term(scope).unwrap()).unwrap(), 65535);
+        // An i32 is an integer like the other types, not a raw term ref.
+        assert_eq!(42i32.to_term(scope).unwrap().get_integer().unwrap(), 42);
+        assert_eq!(f64::try_from(&2.5f64.to_term(scope).unwrap()).unwrap(), 2.5);
+        assert_eq!(
+            i128::try_from(&i128::MIN.to_term(scope).unwrap()).unwrap(),
+            i128::MIN
+        );
+    });
+    sicstus_rs::println!("test_integer_from, Ok");
+}
+
+fn test_text_and_bool() {
+    TermScope::run(|scope| {
+        assert!(bool::try_from(&true.to_term(scope).unwrap()).unwrap());
+        assert_eq!(char::try_from(&'ß'.to_term(scope).unwrap()).unwrap(), 'ß');
+        let term = "hello".to_term(scope).unwrap();
+        assert_eq!(String::try_from(&term).unwrap(), "hello");
+        let term = String::from("wörld").to_term(scope).unwrap();
+        assert_same(&term, "'wörld'");
+    });
+    sicstus_rs::println!("test_text_and_bool, Ok");
+}
+
+fn test_containers() {
+    TermScope::run(|scope| {
+        let term = vec![1u32, 2, 3].to_term(scope).unwrap();
+        assert_same(&term, "[1, 2, 3]");
+        assert_eq!(<[u32; 3]>::try_from(&term).unwrap(), [1, 2, 3]);
+        assert!(<[u32; 2]>::try_from(&term).is_err());
+        let term = Some(7i64).to_term(scope).unwrap();
+        assert_same(&term, "some(7)");
+        assert_eq!(Option::<i64>::try_from(&term).unwrap(), Some(7));
+    });
+    sicstus_rs::println!("test_containers, Ok");
+}
+
+fn test_tuples() {
+    TermScope::run(|scope| {
+        let term = (1i64, "a").to_term(scope).unwrap();
+        assert_same(&term, "1-a");
+        assert_eq!(
+            <(i64, String)>::try_from(&term).unwrap(),
+            (1, String::from("a"))
+        );
+        let (term, _) = TermRef::parse(scope, "(1, b)").unwrap();
+        assert_eq!(
+            <(i64, String)>::try_from(&term).unwrap(),
+            (1, String::from("b"))
+        );
+
+        let term = (1i64, 2.5f64, 'c').to_term(scope).unwrap();
+        assert_same(&term, "(1, 2.5, c)");
+        assert_eq!(<(i64, f64, char)>::try_from(&term).unwrap(), (1, 2.5, 'c'));
+    });
+    sicstus_rs::println!("test_tuples, Ok");
+}
+
+fn test_btree_map() {
+    TermScope::run(|scope| {
+        let map = BTreeMap::from([(String::from("a"), 1i64), (String::from("b"), 2)]);
+        let term = map.to_term(scope).unwrap();
+        assert_same(&term, "[a-1, b-2]");
+        assert_eq!(BTreeMap::<String, i64>::try_from(&term).unwrap(), map);
+    });
+    sicstus_rs::println!("test_btree_map, Ok");
+}
+
+fn test_conversion_errors() {
+    TermScope::run(|scope| {
+        let atom = "foo".to_term(scope).unwrap();
+        assert!(matches!(
+            i64::try_from(&atom),
+            Err(Error::TypeError { expected, .. }) if expected == "integer"
+        ));
+        assert!(matches!(
+            u8::try_from(&256i64.to_term(scope).unwrap()),
+            Err(Error::TypeError { expected, .. }) if expected == "u8"
+        ));
+        assert!(bool::try_from(&atom).is_err());
+        assert!("a\0b".to_term(scope).is_err());
+    });
+    sicstus_rs::println!("test_conversion_errors, Ok");
+}
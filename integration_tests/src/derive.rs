@@ -0,0 +1,75 @@
+use sicstus_rs::{FromTerm, Predicate, TermRef, TermScope, ToTerm};
+
+#[derive(ToTerm, FromTerm, Debug, PartialEq)]
+struct Person {
+    #[term(arg = 3)]
+    age: u8,
+    name: String,
+    #[term(codes)]
+    surname: String,
+    #[term(skip)]
+    visits: usize,
+}
+
+#[derive(ToTerm, FromTerm, Debug, PartialEq)]
+enum Command {
+    Move {
+        x: i64,
+        y: i64,
+    },
+    #[term(name = "say")]
+    Print(String),
+    Stop,
+}
+
+pub fn test_derive() {
+    test_derive_from_term();
+    test_derive_to_term();
+    test_derive_enum();
+}
+
+fn ada() -> Person {
+    Person {
+        age: 36,
+        name: String::from("Ada"),
+        surname: String::from("Lovelace"),
+        visits: 0,
+    }
+}
+
+fn test_derive_from_term() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_person", 1).expect("test_person/1 should exist");
+        let args = pred
+            .call(&[TermRef::new_variable(scope)])
+            .unwrap()
+            .expect("test_person/1 should have a solution");
+        assert_eq!(Person::from_term(&args[0]).unwrap(), ada());
+    });
+    sicstus_rs::println!("test_derive_from_term, Ok");
+}
+
+fn test_derive_to_term() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_person", 1).expect("test_person/1 should exist");
+        let person = Person { visits: 3, ..ada() };
+        assert!(pred.call_once(&[person.to_term(scope).unwrap()]).unwrap());
+    });
+    sicstus_rs::println!("test_derive_to_term, Ok");
+}
+
+fn test_derive_enum() {
+    TermScope::run(|scope| {
+        let commands = vec![
+            Command::Move { x: 1, y: -2 },
+            Command::Print(String::from("hello")),
+            Command::Stop,
+        ];
+        let term = commands.to_term(scope).unwrap();
+        assert_eq!(Vec::<Command>::from_term(&term).unwrap(), commands);
+        let stop = Command::Stop.to_term(scope).unwrap();
+        assert!(stop.is_atom());
+        assert!(Person::from_term(&stop).is_err());
+    });
+    sicstus_rs::println!("test_derive_enum, Ok");
+}
@@ -0,0 +1,135 @@
+use sicstus_rs::Error;
+use sicstus_rs::{
+    define_predicate, Atom, ErrorTerm, IsoError, Predicate, PrologException, Query, Term, TermRef,
+    TermScope, ToTerm,
+};
+use std::ffi::CString;
+
+pub fn test_exception() {
+    test_instantiation_error();
+    test_type_error();
+    test_custom_exception();
+    test_exception_in_query();
+    test_error_term_roundtrip();
+    test_error_caught_in_prolog();
+}
+
+fn raise(kind: &str) -> PrologException {
+    let pred = Predicate::new("user", "test_throw", 1).expect("test_throw/1 should exist");
+    let kind = Atom::from(kind);
+    TermScope::run(|scope| match pred.call_once(&[kind.to_term(scope).unwrap()]) {
+        Err(Error::Exception { exception, .. }) => exception,
+        other => panic!("expected an exception, got {:?}", other),
+    })
+}
+
+fn test_instantiation_error() {
+    let exception = raise("instantiation");
+    assert!(matches!(
+        exception.iso_error(),
+        Some(IsoError::InstantiationError)
+    ));
+    assert!(exception.context().is_some());
+    sicstus_rs::println!("test_instantiation_error, Ok");
+}
+
+fn test_type_error() {
+    let exception = raise("type");
+    match exception.iso_error() {
+        Some(IsoError::TypeError { expected, culprit }) => {
+            assert_eq!(expected, "integer");
+            assert_eq!(culprit, &Term::Atom(String::from("foo")));
+        }
+        other => panic!("expected a type error, got {:?}", other),
+    }
+    sicstus_rs::println!("test_type_error, Ok");
+}
+
+fn test_custom_exception() {
+    let exception = raise("custom");
+    assert!(exception.is_custom());
+    assert!(exception.context().is_none());
+    assert_eq!(exception.term(), &Term::Atom(String::from("my_exception")));
+    sicstus_rs::println!("test_custom_exception, Ok");
+}
+
+fn test_exception_in_query() {
+    let pred = Predicate::new("user", "test_throw", 1).expect("test_throw/1 should exist");
+    let kind = Atom::from("custom");
+    TermScope::run(|scope| {
+        let mut solutions = Query::open(&pred, &[kind.to_term(scope).unwrap()])
+            .unwrap()
+            .solutions();
+        assert!(matches!(
+            solutions.next(),
+            Some(Err(Error::Exception { .. }))
+        ));
+        assert!(solutions.next().is_none());
+    });
+    sicstus_rs::println!("test_exception_in_query, Ok");
+}
+
+fn test_error_term_roundtrip() {
+    TermScope::run(|scope| {
+        let term = ErrorTerm::type_error("integer", Term::Float(1.5))
+            .predicate("my_pred", 2)
+            .message("expected an integer")
+            .to_term(scope)
+            .unwrap();
+        let exception = PrologException::from_term(&term).unwrap();
+        match exception.iso_error() {
+            Some(IsoError::TypeError { expected, culprit }) => {
+                assert_eq!(expected, "integer");
+                assert_eq!(culprit, &Term::Float(1.5));
+            }
+            other => panic!("expected a type error, got {:?}", other),
+        }
+        let context = TermRef::parse(scope, "context(my_pred/2, 'expected an integer')")
+            .unwrap()
+            .0
+            .to_term()
+            .unwrap();
+        assert_eq!(exception.context(), Some(&context));
+    });
+    sicstus_rs::println!("test_error_term_roundtrip, Ok");
+}
+
+fn test_error_caught_in_prolog() {
+    TermScope::run(|scope| {
+        define_predicate("user", "rust_raise", 1, |args| {
+            let error = match args[0].get_string()?.as_str() {
+                "type" => Error::TypeError {
+                    expected: String::from("integer"),
+                    culprit: args[0].to_term()?,
+                },
+                "range" => Error::ArgumentOutOfRange { index: 3, arity: 2 },
+                "arity" => Error::ArityMismatch {
+                    expected: 2,
+                    found: 1,
+                },
+                "conversion" => Error::Conversion(String::from("too big")),
+                "nul" => Error::InteriorNul(CString::new("a\0b").unwrap_err()),
+                "closed" => Error::QueryClosed,
+                _ => Error::NoMoreSolutions,
+            };
+            Err(error)
+        })
+        .unwrap();
+        let call = Predicate::new("prolog", "call", 1).unwrap();
+        for (kind, pattern) in [
+            ("type", "error(type_error(integer, type), _)"),
+            ("range", "error(domain_error(argument_index, 3), _)"),
+            ("arity", "error(domain_error(arity, 1), _)"),
+            ("conversion", "error(representation_error(conversion), _)"),
+            ("nul", "error(domain_error(text_without_nul, [0'a, 0, 0'b]), _)"),
+            ("closed", "error(permission_error(access, query, closed), _)"),
+            ("other", "error(system_error, _)"),
+        ] {
+            // catch/3 only succeeds if the exception raised from Rust matches the ISO error term.
+            let goal = format!("catch(rust_raise({}), {}, true)", kind, pattern);
+            let (goal, _) = TermRef::parse(scope, &goal).unwrap();
+            assert!(call.call_once(&[goal]).unwrap(), "{} should be caught", kind);
+        }
+    });
+    sicstus_rs::println!("test_error_caught_in_prolog, Ok");
+}
@@ -0,0 +1,193 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use sicstus_rs::{
+    define_predicate, Atom, Compound, Error, FromTerm, IsoError, Predicate, TermRef, TermScope,
+    ToTerm,
+};
+
+pub fn test_foreign() {
+    test_define_predicate();
+    test_define_predicate_failure();
+    test_define_predicate_error();
+    test_define_predicate_panic();
+    test_foreign_attribute();
+    test_foreign_attribute_error();
+}
+
+fn test_define_predicate() {
+    define_predicate("user", "rust_add", 3, |args| {
+        let sum = i64::from_term(&args[0])? + i64::from_term(&args[1])?;
+        args[2].unify(&sum.to_term(args[2].scope())?)?;
+        Ok(true)
+    })
+    .unwrap();
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "rust_add", 3).unwrap();
+        let args = pred
+            .call(&[
+                1.to_term(scope).unwrap(),
+                2.to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+            ])
+            .unwrap()
+            .expect("rust_add/3 should succeed");
+        assert_eq!(i64::from_term(&args[2]).unwrap(), 3);
+
+        // The closure keeps its state between calls.
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        define_predicate("user", "rust_count", 0, move |args| {
+            assert!(args.is_empty());
+            counter.set(counter.get() + 1);
+            Ok(true)
+        })
+        .unwrap();
+        let pred = Predicate::new("user", "rust_count", 0).unwrap();
+        assert!(pred.call_once(&[]).unwrap());
+        assert!(pred.call_once(&[]).unwrap());
+        assert_eq!(calls.get(), 2);
+    });
+    sicstus_rs::println!("test_define_predicate, Ok");
+}
+
+fn test_define_predicate_failure() {
+    define_predicate("user", "rust_positive", 1, |args| {
+        Ok(i64::from_term(&args[0])? > 0)
+    })
+    .unwrap();
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "rust_positive", 1).unwrap();
+        assert!(pred.call_once(&[5.to_term(scope).unwrap()]).unwrap());
+        assert!(!pred.call_once(&[(-5).to_term(scope).unwrap()]).unwrap());
+    });
+    sicstus_rs::println!("test_define_predicate_failure, Ok");
+}
+
+fn test_define_predicate_error() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "rust_positive", 1).unwrap();
+        match pred.call_once(&["foo".to_term(scope).unwrap()]) {
+            Err(Error::Exception { exception, .. }) => match exception.iso_error() {
+                Some(IsoError::TypeError { expected, .. }) => assert_eq!(expected, "integer"),
+                other => panic!("expected a type error, got {:?}", other),
+            },
+            other => panic!("expected an exception, got {:?}", other),
+        }
+    });
+    sicstus_rs::println!("test_define_predicate_error, Ok");
+}
+
+fn test_define_predicate_panic() {
+    define_predicate("user", "rust_panic", 0, |_| panic!("boom")).unwrap();
+    let pred = Predicate::new("user", "rust_panic", 0).unwrap();
+    match pred.call_once(&[]) {
+        Err(Error::Exception { exception, .. }) => {
+            assert!(matches!(exception.iso_error(), Some(IsoError::SystemError)));
+        }
+        other => panic!("expected an exception, got {:?}", other),
+    }
+    sicstus_rs::println!("test_define_predicate_panic, Ok");
+}
+
+#[sicstus_rs::foreign(name = "rust_foreign_add", args(+integer, +integer, -integer))]
+fn foreign_add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+#[sicstus_rs::foreign(name = "rust_foreign_greet", args(+string, -string))]
+fn foreign_greet(name: String) -> String {
+    format!("Hello, {}!", name)
+}
+
+#[sicstus_rs::foreign(
+    name = "rust_foreign_divmod",
+    args(+integer, +integer, -integer, -integer)
+)]
+fn foreign_divmod(a: i64, b: i64) -> Result<(i64, i64), Error> {
+    if b == 0 {
+        return Err(Error::Conversion(String::from("division by zero")));
+    }
+    Ok((a / b, a % b))
+}
+
+#[sicstus_rs::foreign(name = "rust_foreign_wrap", args(+atom, +term, -term))]
+fn foreign_wrap(name: Atom, value: TermRef) -> Result<TermRef, Error> {
+    let scope = value.scope();
+    Compound::new(name.as_str()).arg(value).build(scope)
+}
+
+fn call_foreign<'s>(name: &str, args: &[TermRef<'s>]) -> Result<Vec<TermRef<'s>>, Error> {
+    let pred = Predicate::new("user", name, args.len())?;
+    Ok(pred
+        .call(args)?
+        .expect("the foreign predicate should succeed"))
+}
+
+fn test_foreign_attribute() {
+    TermScope::run(|scope| {
+        let args = call_foreign(
+            "rust_foreign_add",
+            &[
+                1.to_term(scope).unwrap(),
+                2.to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+            ],
+        )
+        .unwrap();
+        assert_eq!(i64::from_term(&args[2]).unwrap(), 3);
+
+        let args = call_foreign(
+            "rust_foreign_greet",
+            &["Ada".to_term(scope).unwrap(), TermRef::new_variable(scope)],
+        )
+        .unwrap();
+        assert_eq!(String::from_term(&args[1]).unwrap(), "Hello, Ada!");
+
+        let args = call_foreign(
+            "rust_foreign_divmod",
+            &[
+                7.to_term(scope).unwrap(),
+                2.to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+                TermRef::new_variable(scope),
+            ],
+        )
+        .unwrap();
+        assert_eq!(i64::from_term(&args[2]).unwrap(), 3);
+        assert_eq!(i64::from_term(&args[3]).unwrap(), 1);
+
+        let args = call_foreign(
+            "rust_foreign_wrap",
+            &[
+                "point".to_term(scope).unwrap(),
+                (1, 2).to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+            ],
+        )
+        .unwrap();
+        let (name, arity) = args[2].get_functor().unwrap();
+        assert_eq!((name.as_str(), arity), ("point", 1));
+        assert_eq!(
+            <(i64, i64)>::from_term(&args[2].get_arg(1).unwrap()).unwrap(),
+            (1, 2)
+        );
+    });
+    sicstus_rs::println!("test_foreign_attribute, Ok");
+}
+
+fn test_foreign_attribute_error() {
+    TermScope::run(|scope| {
+        let result = call_foreign(
+            "rust_foreign_divmod",
+            &[
+                7.to_term(scope).unwrap(),
+                0.to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+                TermRef::new_variable(scope),
+            ],
+        );
+        assert!(matches!(result, Err(Error::Exception { .. })));
+    });
+    sicstus_rs::println!("test_foreign_attribute_error, Ok");
+}
@@ -0,0 +1,9 @@
+use std::cmp::Ordering;
+
+use sicstus_rs::TermRef;
+
+/// Check that the term is identical to the parsed text.
+pub fn assert_same(term: &TermRef, text: &str) {
+    let (expected, _) = TermRef::parse(term.scope(), text).unwrap();
+    assert_eq!(term.cmp(&expected), Ordering::Equal, "expected {}", text);
+}
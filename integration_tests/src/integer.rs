@@ -0,0 +1,84 @@
+use num_bigint::{BigInt, BigUint};
+use sicstus_rs::{FromTerm, Predicate, TermRef, TermScope, ToTerm};
+
+pub fn test_integer() {
+    test_i128();
+    test_u128();
+    test_bigint();
+    test_integer_overflow();
+}
+
+/// Let Prolog add one, so both directions are checked against Prolog's own arithmetic.
+fn succ(term: TermRef) -> TermRef {
+    let scope = term.scope();
+    let pred = Predicate::new("user", "test_succ", 2).expect("test_succ/2 should exist");
+    let args = pred
+        .call(&[term, TermRef::new_variable(scope)])
+        .unwrap()
+        .expect("test_succ/2 should succeed");
+    args[1].clone()
+}
+
+fn test_i128() {
+    TermScope::run(|scope| {
+        for value in [
+            0,
+            -1,
+            i64::MAX as i128,
+            i64::MIN as i128 - 2,
+            1 << 100,
+            -(1 << 100),
+            i128::MAX - 1,
+            i128::MIN,
+        ] {
+            let term = value.to_term(scope).unwrap();
+            assert_eq!(i128::from_term(&term).unwrap(), value);
+            assert_eq!(i128::from_term(&succ(term)).unwrap(), value + 1);
+        }
+    });
+    sicstus_rs::println!("test_i128, Ok");
+}
+
+fn test_u128() {
+    TermScope::run(|scope| {
+        for value in [0, u64::MAX as u128, 1 << 127, u128::MAX - 1] {
+            let term = value.to_term(scope).unwrap();
+            assert_eq!(u128::from_term(&term).unwrap(), value);
+            assert_eq!(u128::from_term(&succ(term)).unwrap(), value + 1);
+        }
+        assert_eq!(
+            u64::from_term(&u64::MAX.to_term(scope).unwrap()).unwrap(),
+            u64::MAX
+        );
+    });
+    sicstus_rs::println!("test_u128, Ok");
+}
+
+fn test_bigint() {
+    TermScope::run(|scope| {
+        let value: BigInt = "-123456789012345678901234567890123456789012345678901234567890"
+            .parse()
+            .unwrap();
+        let term = value.to_term(scope).unwrap();
+        assert_eq!(BigInt::from_term(&term).unwrap(), value);
+        assert_eq!(BigInt::from_term(&succ(term)).unwrap(), value + 1);
+
+        let value = BigUint::from(u128::MAX) * 3u32;
+        let term = value.to_term(scope).unwrap();
+        assert_eq!(BigUint::from_term(&term).unwrap(), value);
+        assert!(BigUint::from_term(&(-1).to_term(scope).unwrap()).is_err());
+    });
+    sicstus_rs::println!("test_bigint, Ok");
+}
+
+fn test_integer_overflow() {
+    TermScope::run(|scope| {
+        let term = succ(i128::MAX.to_term(scope).unwrap());
+        assert!(i128::from_term(&term).is_err());
+        assert_eq!(u128::from_term(&term).unwrap(), 1 << 127);
+        assert!(u128::from_term(&succ(u128::MAX.to_term(scope).unwrap())).is_err());
+        assert!(i64::from_term(&succ(i64::MAX.to_term(scope).unwrap())).is_err());
+        assert!(u128::from_term(&(-1i64).to_term(scope).unwrap()).is_err());
+    });
+    sicstus_rs::println!("test_integer_overflow, Ok");
+}
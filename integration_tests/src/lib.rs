@@ -1,16 +1,62 @@
 mod sys;
 mod list;
+mod query;
+mod exception;
+mod term_serde;
+mod derive;
+mod term;
+mod parse;
+mod write;
+mod integer;
+mod conversion;
+mod view;
+mod compound;
+mod scope;
+mod foreign;
+mod runtime;
+mod load;
+mod helpers;
 
 use crate::sys::sys_tests;
 use crate::list::test_list;
+use crate::query::test_query;
+use crate::exception::test_exception;
+use crate::term_serde::test_serde;
+use crate::derive::test_derive;
+use crate::term::test_term;
+use crate::parse::test_parse;
+use crate::write::test_write;
+use crate::integer::test_integer;
+use crate::conversion::test_conversion;
+use crate::view::test_view;
+use crate::compound::test_compound;
+use crate::scope::test_scope;
+use crate::foreign::test_foreign;
+use crate::runtime::test_runtime;
+use crate::load::test_load;
 
 
-#[no_mangle]
-pub extern "C" fn rust_main() {
+#[sicstus_rs::foreign]
+fn rust_main() {
     run_tests();
 }
 
 fn run_tests() {
     sys_tests();
     test_list();
+    test_query();
+    test_exception();
+    test_serde();
+    test_derive();
+    test_term();
+    test_parse();
+    test_write();
+    test_integer();
+    test_conversion();
+    test_view();
+    test_compound();
+    test_scope();
+    test_foreign();
+    test_runtime();
+    test_load();
 }
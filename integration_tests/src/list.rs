@@ -1,16 +1,88 @@
-use sicstus_rs::TermRef;
+use sicstus_rs::{Error, PrologList, TermRef, TermScope};
 
 pub fn test_list() {
     test_vec_roundtrip();
+    test_list_builder();
+    test_list_iter_errors();
+    test_difference_list();
 }
 
 pub fn test_vec_roundtrip() {
-    let v: Vec<i32> = vec![1,2,3,4,5];
-    let t: TermRef = v.iter().map(|x| TermRef::new_integer(*x as i64)).collect();
-    let v2: Vec<i32> = t.into_iter().map(|x| {
-        x.get_integer().expect("There should be an int here") as i32
-    } ).collect();
-    let v: Vec<i32> = v.into_iter().rev().collect();
-    assert_eq!(v, v2);
+    TermScope::run(|scope| {
+        let v: Vec<i32> = vec![1,2,3,4,5];
+        let t = v.iter()
+            .map(|x| TermRef::new_integer(scope, *x as i64))
+            .collect::<PrologList>()
+            .build(scope)
+            .unwrap();
+        let v2: Vec<i32> = t.into_iter().map(|x| {
+            x.get_integer().expect("There should be an int here") as i32
+        } ).collect();
+        assert_eq!(v, v2);
+    });
     println!("test_vec_roundtrip, Ok");
 }
+
+fn integers(term: &TermRef) -> Result<Vec<i64>, Error> {
+    term.list_iter().map(|item| item?.get_integer()).collect()
+}
+
+fn test_list_builder() {
+    TermScope::run(|scope| {
+        let list = PrologList::new()
+            .push(TermRef::new_integer(scope, 1))
+            .push(TermRef::new_integer(scope, 2))
+            .build(scope)
+            .unwrap();
+        assert!(list.is_proper_list());
+        assert_eq!(list.list_len().unwrap(), 2);
+        assert_eq!(integers(&list).unwrap(), [1, 2]);
+
+        let empty = PrologList::new().build(scope).unwrap();
+        assert!(empty.is_proper_list());
+        assert_eq!(empty.list_len().unwrap(), 0);
+    });
+    println!("test_list_builder, Ok");
+}
+
+fn test_list_iter_errors() {
+    TermScope::run(|scope| {
+        let tail = TermRef::new_variable(scope);
+        let partial = PrologList::from(vec![TermRef::new_integer(scope, 1)])
+            .tail(tail)
+            .build(scope)
+            .unwrap();
+        assert!(!partial.is_proper_list());
+        let items: Vec<_> = partial.list_iter().collect();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1], Err(Error::InstantiationError)));
+
+        let (improper, _) = TermRef::parse(scope, "[1, 2 | foo]").unwrap();
+        assert!(matches!(
+            integers(&improper),
+            Err(Error::TypeError { ref expected, .. }) if expected == "list"
+        ));
+        assert!(matches!(
+            TermRef::new_integer(scope, 3).list_len(),
+            Err(Error::TypeError { .. })
+        ));
+    });
+    println!("test_list_iter_errors, Ok");
+}
+
+fn test_difference_list() {
+    TermScope::run(|scope| {
+        let (list, hole) = PrologList::from(vec![TermRef::new_integer(scope, 1)])
+            .build_difference(scope)
+            .unwrap();
+        let rest = [2, 3]
+            .into_iter()
+            .map(|i| TermRef::new_integer(scope, i))
+            .collect::<PrologList>()
+            .build(scope)
+            .unwrap();
+        hole.unify(&rest).unwrap();
+        assert_eq!(integers(&list).unwrap(), [1, 2, 3]);
+    });
+    println!("test_difference_list, Ok");
+}
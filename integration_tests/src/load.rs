@@ -0,0 +1,46 @@
+use std::{env, fs};
+
+use sicstus_rs::{sys, Error, IsoError, Predicate, TermRef, TermScope};
+
+pub fn test_load() {
+    test_expand_file_name();
+    test_load_file();
+    test_load_missing_file();
+}
+
+fn test_expand_file_name() {
+    let path = sys::sp_expand_file_name("integration_tests.pl", 0).unwrap();
+    assert!(path.starts_with('/'));
+    assert!(path.ends_with("/integration_tests.pl"));
+    sicstus_rs::println!("test_expand_file_name, Ok");
+}
+
+fn test_load_file() {
+    TermScope::run(|scope| {
+        let path = env::temp_dir().join("sicstus_rs_test_load.pl");
+        fs::write(&path, "test_loaded(42).\n").unwrap();
+        sys::sp_load(path.to_str().unwrap()).unwrap();
+        let pred = Predicate::new("user", "test_loaded", 1).unwrap();
+        let solution = pred.call(&[TermRef::new_variable(scope)]).unwrap();
+        assert_eq!(solution.unwrap()[0].get_integer().unwrap(), 42);
+        fs::remove_file(path).unwrap();
+    });
+    sicstus_rs::println!("test_load_file, Ok");
+}
+
+fn test_load_missing_file() {
+    match sys::sp_load("/nonexistent/missing.pl") {
+        Err(Error::Load {
+            file,
+            exception: Some(exception),
+        }) => {
+            assert_eq!(file, "/nonexistent/missing.pl");
+            assert!(matches!(
+                exception.iso_error(),
+                Some(IsoError::ExistenceError { .. })
+            ));
+        }
+        other => panic!("expected a load error, got {:?}", other),
+    }
+    sicstus_rs::println!("test_load_missing_file, Ok");
+}
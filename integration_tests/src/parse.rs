@@ -0,0 +1,70 @@
+use sicstus_rs::{Error, Predicate, Term, TermRef, TermScope};
+
+pub fn test_parse() {
+    test_parse_bindings();
+    test_parse_prebound();
+    test_parse_anonymous();
+    test_parse_syntax_error();
+}
+
+fn test_parse_bindings() {
+    TermScope::run(|scope| {
+        let (term, variables) = TermRef::parse(scope, "foo(X, Y, bar, X)").unwrap();
+        assert_eq!(variables.len(), 2);
+        let expected = Term::Compound {
+            name: String::from("foo"),
+            args: vec![
+                Term::Var(0),
+                Term::Var(1),
+                Term::Atom(String::from("bar")),
+                Term::Var(0),
+            ],
+        };
+        assert_eq!(term.to_term().unwrap(), expected);
+        assert!(variables["X"].unify(&TermRef::new_integer(scope, 1)).is_ok());
+        assert_eq!(term.get_arg(4).unwrap().get_integer().unwrap(), 1);
+    });
+    sicstus_rs::println!("test_parse_bindings, Ok");
+}
+
+fn test_parse_prebound() {
+    TermScope::run(|scope| {
+        let one = TermRef::new_integer(scope, 1);
+        let (term, _) = TermRef::parse_with(scope, "test_fact(N).", &[("N", &one)]).unwrap();
+        let pred = Predicate::new("prolog", "call", 1).unwrap();
+        assert!(pred.call_once(&[term]).unwrap());
+        assert!(TermRef::parse_with(scope, "f(X)", &[("Y", &one)]).is_err());
+    });
+    sicstus_rs::println!("test_parse_prebound, Ok");
+}
+
+fn test_parse_anonymous() {
+    TermScope::run(|scope| {
+        // Anonymous variables come before the named ones, which must still get the right bindings.
+        let (term, variables) = TermRef::parse(scope, "f(_, X, _Y, X)").unwrap();
+        assert_eq!(variables.len(), 2);
+        assert!(variables.contains_key("_Y"));
+        variables["X"].unify(&TermRef::new_integer(scope, 7)).unwrap();
+        assert!(term.get_arg(1).unwrap().is_variable());
+        assert_eq!(term.get_arg(2).unwrap().get_integer().unwrap(), 7);
+        assert!(term.get_arg(3).unwrap().is_variable());
+        assert_eq!(term.get_arg(4).unwrap().get_integer().unwrap(), 7);
+
+        let one = TermRef::new_integer(scope, 1);
+        let (term, _) = TermRef::parse_with(scope, "f(_, X, _Y, X)", &[("X", &one)]).unwrap();
+        assert!(term.get_arg(1).unwrap().is_variable());
+        assert_eq!(term.get_arg(2).unwrap().get_integer().unwrap(), 1);
+    });
+    sicstus_rs::println!("test_parse_anonymous, Ok");
+}
+
+fn test_parse_syntax_error() {
+    TermScope::run(|scope| {
+        match TermRef::parse(scope, "foo(a,\n  b c)") {
+            Err(Error::Syntax { line, .. }) => assert_eq!(line, 2),
+            Err(e) => panic!("expected a syntax error, got {}", e),
+            Ok(_) => panic!("expected a syntax error"),
+        }
+    });
+    sicstus_rs::println!("test_parse_syntax_error, Ok");
+}
@@ -0,0 +1,108 @@
+use sicstus_rs::{Error, Predicate, Query, TermRef, TermScope};
+
+pub fn test_query() {
+    test_query_solutions();
+    test_query_drop_cuts();
+    test_solutions();
+    test_solutions_take();
+    test_call_once();
+    test_predicate_from_indicator();
+}
+
+fn test_query_solutions() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_fact", 1).expect("test_fact/1 should exist");
+        let x = TermRef::new_variable(scope);
+        let mut query = Query::open(&pred, &[x]).unwrap();
+        let mut solutions = Vec::new();
+        while query.next_solution().is_ok() {
+            solutions.push(query.args()[0].get_integer().unwrap());
+        }
+        query.close().unwrap();
+        assert_eq!(solutions, vec![1, 2, 3]);
+    });
+    sicstus_rs::println!("test_query_solutions, Ok");
+}
+
+fn test_query_drop_cuts() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_fact", 1).expect("test_fact/1 should exist");
+        let x = TermRef::new_variable(scope);
+        {
+            let mut query = Query::open(&pred, std::slice::from_ref(&x)).unwrap();
+            query.next_solution().unwrap();
+        }
+        assert_eq!(x.get_integer().unwrap(), 1);
+    });
+    sicstus_rs::println!("test_query_drop_cuts, Ok");
+}
+
+fn test_solutions() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_fact", 1).expect("test_fact/1 should exist");
+        let x = TermRef::new_variable(scope);
+        let solutions: Vec<i64> = Query::open(&pred, &[x])
+            .unwrap()
+            .solutions()
+            .map(|args| args.unwrap()[0].get_integer().unwrap())
+            .collect();
+        assert_eq!(solutions, vec![1, 2, 3]);
+    });
+    sicstus_rs::println!("test_solutions, Ok");
+}
+
+fn test_solutions_take() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_fact", 1).expect("test_fact/1 should exist");
+        let x = TermRef::new_variable(scope);
+        let mut solutions = Query::open(&pred, std::slice::from_ref(&x))
+            .unwrap()
+            .solutions()
+            .take(2);
+        assert_eq!(
+            solutions.next().unwrap().unwrap()[0].get_integer().unwrap(),
+            1
+        );
+        assert_eq!(
+            solutions.next().unwrap().unwrap()[0].get_integer().unwrap(),
+            2
+        );
+        assert!(solutions.next().is_none());
+        // The query was cut after the second solution, so its bindings are kept.
+        assert_eq!(x.get_integer().unwrap(), 2);
+    });
+    sicstus_rs::println!("test_solutions_take, Ok");
+}
+
+fn test_call_once() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_fact", 1).expect("test_fact/1 should exist");
+        assert!(pred.call_once(&[TermRef::new_integer(scope, 2)]).unwrap());
+        assert!(!pred.call_once(&[TermRef::new_integer(scope, 4)]).unwrap());
+        let x = TermRef::new_variable(scope);
+        assert!(pred.call_for_side_effects(std::slice::from_ref(&x)).unwrap());
+        assert!(x.is_variable());
+        let args = pred
+            .call(&[x])
+            .unwrap()
+            .expect("test_fact/1 should have a solution");
+        assert_eq!(args[0].get_integer().unwrap(), 1);
+    });
+    sicstus_rs::println!("test_call_once, Ok");
+}
+
+fn test_predicate_from_indicator() {
+    TermScope::run(|scope| {
+        let pred = Predicate::from_indicator("test_fact/1").expect("test_fact/1 should exist");
+        assert!(pred.call_once(&[TermRef::new_integer(scope, 3)]).unwrap());
+        assert!(matches!(
+            Predicate::from_indicator("user:no_such_predicate/7"),
+            Err(Error::PredicateNotFound(_))
+        ));
+        assert!(matches!(
+            Predicate::from_indicator("test_fact"),
+            Err(Error::MalformedIndicator(_))
+        ));
+    });
+    sicstus_rs::println!("test_predicate_from_indicator, Ok");
+}
@@ -0,0 +1,12 @@
+use sicstus_rs::{Error, Runtime, RuntimeOptions};
+
+pub fn test_runtime() {
+    test_runtime_already_initialized();
+}
+
+fn test_runtime_already_initialized() {
+    // The tests run inside Prolog, which started the runtime.
+    let result = Runtime::init(RuntimeOptions::new().arg("integration_tests"));
+    assert!(matches!(result, Err(Error::AlreadyInitialized)));
+    sicstus_rs::println!("test_runtime_already_initialized, Ok");
+}
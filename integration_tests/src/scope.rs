@@ -0,0 +1,103 @@
+use sicstus_rs::{Error, FromTerm, PrologList, Term, TermRef, TermScope, ToTerm};
+
+pub fn test_scope() {
+    test_scope_loop();
+    test_scope_keeps_result();
+    test_scope_loop_keeps_results();
+    test_scope_nested();
+    test_scope_error();
+}
+
+fn test_scope_loop() {
+    TermScope::run(|scope| {
+        let list = (0..100_000)
+            .map(|i| TermRef::new_integer(scope, i))
+            .collect::<PrologList>()
+            .build(scope)
+            .unwrap();
+        let mut sum = 0;
+        for item in list.list_iter() {
+            let item = item.unwrap();
+            // Each iteration creates term refs that are reclaimed right away.
+            sum += TermScope::run(|inner| {
+                let term = (item.get_integer().unwrap(), "x").to_term(inner).unwrap();
+                term.arg::<i64>(1).unwrap()
+            });
+        }
+        assert_eq!(sum, (0..100_000).sum::<i64>());
+    });
+    sicstus_rs::println!("test_scope_loop, Ok");
+}
+
+fn test_scope_keeps_result() {
+    TermScope::run(|scope| {
+        let kept = scope
+            .run_term(|inner| {
+                let _garbage: Vec<TermRef> =
+                    (0..100).map(|i| TermRef::new_integer(inner, i)).collect();
+                ("kept", vec![1, 2, 3]).to_term(inner)
+            })
+            .unwrap();
+        // New term refs reuse the reclaimed slots.
+        let _fresh: Vec<TermRef> = (0..100).map(|_| TermRef::new_integer(scope, -1)).collect();
+        assert_eq!(
+            <(String, Vec<i64>)>::from_term(&kept).unwrap(),
+            (String::from("kept"), vec![1, 2, 3])
+        );
+    });
+    sicstus_rs::println!("test_scope_keeps_result, Ok");
+}
+
+fn test_scope_loop_keeps_results() {
+    TermScope::run(|scope| {
+        // Each iteration keeps a term and resets the term refs after it, which must not free the earlier results.
+        let kept: Vec<TermRef> = (0..100)
+            .map(|i| {
+                scope
+                    .run_term(|inner| {
+                        let _garbage: Vec<TermRef> =
+                            (0..10).map(|j| TermRef::new_integer(inner, j)).collect();
+                        (i, "item").to_term(inner)
+                    })
+                    .unwrap()
+            })
+            .collect();
+        let _fresh: Vec<TermRef> = (0..1000).map(|_| TermRef::new_integer(scope, -1)).collect();
+        for (i, term) in kept.iter().enumerate() {
+            let (index, name) = <(i64, String)>::from_term(term).unwrap();
+            assert_eq!((index, name.as_str()), (i as i64, "item"));
+        }
+    });
+    sicstus_rs::println!("test_scope_loop_keeps_results, Ok");
+}
+
+fn test_scope_nested() {
+    TermScope::run(|scope| {
+        let outer = scope
+            .run_term(|middle| {
+                let inner = middle.run_term(|inner| 42.to_term(inner))?;
+                let _garbage = TermRef::new_integer(middle, 0);
+                (inner, "outer").to_term(middle)
+            })
+            .unwrap();
+        let _fresh: Vec<TermRef> = (0..10).map(|_| TermRef::new_integer(scope, -1)).collect();
+        assert_eq!(
+            <(i64, String)>::from_term(&outer).unwrap(),
+            (42, String::from("outer"))
+        );
+    });
+    sicstus_rs::println!("test_scope_nested, Ok");
+}
+
+fn test_scope_error() {
+    // Errors own their culprit, so they can leave the scope that raised them.
+    let result: Result<i64, Error> =
+        TermScope::run(|scope| i64::from_term(&"foo".to_term(scope)?));
+    match result {
+        Err(Error::TypeError { culprit, .. }) => {
+            assert_eq!(culprit, Term::Atom(String::from("foo")))
+        }
+        other => panic!("expected a type error, got {:?}", other),
+    }
+    sicstus_rs::println!("test_scope_error, Ok");
+}
@@ -1,10 +1,14 @@
 use core::cmp::Ordering;
 use sicstus_rs::sys::*;
+use sicstus_rs::{Atom, Error, TermRef, TermScope, ToTerm};
 
 pub fn sys_tests() {
     test_sp_atom_from_string();
     test_sp_atom_length();
     test_sp_compare();
+    test_utf8_atoms();
+    test_utf8_strings();
+    test_interior_nul();
 }
 
 fn test_sp_atom_from_string() {
@@ -33,3 +37,46 @@ fn test_sp_compare() {
     assert_eq!(Ordering::Less, sp_compare(t1,t2));
     sicstus_rs::println!("test_sp_compare, Ok");
 }
+
+fn test_utf8_atoms() {
+    for name in ["_ÖALKSDFJÖLK-", "grüße", "🦀"] {
+        let atom = sp_atom_from_string(name).unwrap();
+        assert_eq!(sp_string_from_atom(atom), name);
+        let atom = Atom::from(name);
+        assert_eq!(atom.as_cstr().to_str().unwrap(), name);
+        assert_eq!(atom.clone().as_str(), name);
+    }
+    assert_eq!(sp_atom_length(sp_atom_from_string("🦀").unwrap()), "🦀".len());
+    sicstus_rs::println!("test_utf8_atoms, Ok");
+}
+
+fn test_utf8_strings() {
+    TermScope::run(|scope| {
+        let nil = "[]".to_term(scope).unwrap();
+        let mut term = TermRef::new(scope);
+        term.put_list_codes(&nil, "Ärger 🦀").unwrap();
+        assert_eq!(term.get_list_codes().unwrap(), "Ärger 🦀");
+
+        let mut term = TermRef::new(scope);
+        term.put_list_n_codes(&nil, 2, "Ärger").unwrap();
+        assert_eq!(term.get_list_codes().unwrap(), "Ä");
+        assert!(TermRef::new(scope)
+            .put_list_n_codes(&nil, 1, "Ärger")
+            .is_err());
+
+        let mut term = TermRef::new(scope);
+        term.put_number_codes("123456789012345678901234567890").unwrap();
+        assert!(term.is_integer());
+    });
+    sicstus_rs::println!("test_utf8_strings, Ok");
+}
+
+fn test_interior_nul() {
+    assert!(matches!(
+        sp_atom_from_string("a\0b"),
+        Err(Error::InteriorNul(_))
+    ));
+    assert!(matches!(Atom::try_new("a\0b"), Err(Error::InteriorNul(_))));
+    assert_eq!(Atom::try_new("ab").unwrap().name(), "ab");
+    sicstus_rs::println!("test_interior_nul, Ok");
+}
@@ -0,0 +1,63 @@
+use sicstus_rs::{Predicate, Term, TermScope};
+
+pub fn test_term() {
+    test_term_roundtrip();
+    test_term_shared_variables();
+    test_term_long_list();
+    test_term_many_variables();
+}
+
+fn test_term_roundtrip() {
+    TermScope::run(|scope| {
+        let term = Term::Compound {
+            name: String::from("point"),
+            args: vec![
+                Term::Integer(1),
+                Term::Float(2.5),
+                Term::Atom(String::from("origin")),
+                Term::BigInt(String::from("123456789012345678901234567890")),
+                Term::PartialList(vec![Term::Integer(1)], Box::new(Term::Var(0))),
+            ],
+        };
+        let term_ref = term.to_term_ref(scope).unwrap();
+        assert_eq!(term_ref.to_term().unwrap(), term);
+    });
+    sicstus_rs::println!("test_term_roundtrip, Ok");
+}
+
+fn test_term_shared_variables() {
+    TermScope::run(|scope| {
+        let term = Term::Compound {
+            name: String::from("f"),
+            args: vec![Term::Var(7), Term::Var(7)],
+        };
+        let term_ref = term.to_term_ref(scope).unwrap();
+        let pred = Predicate::new("user", "test_shared", 1).expect("test_shared/1 should exist");
+        assert!(pred.call_once(std::slice::from_ref(&term_ref)).unwrap());
+        let expected = Term::Compound {
+            name: String::from("f"),
+            args: vec![Term::Var(0), Term::Var(0)],
+        };
+        assert_eq!(term_ref.to_term().unwrap(), expected);
+    });
+    sicstus_rs::println!("test_term_shared_variables, Ok");
+}
+
+fn test_term_long_list() {
+    TermScope::run(|scope| {
+        let term = Term::List((0..1_000_000).map(Term::Integer).collect());
+        let term_ref = term.to_term_ref(scope).unwrap();
+        assert_eq!(term_ref.to_term().unwrap(), term);
+    });
+    sicstus_rs::println!("test_term_long_list, Ok");
+}
+
+fn test_term_many_variables() {
+    TermScope::run(|scope| {
+        // Each variable is numbered once, so this takes a few seconds at most.
+        let term = Term::List((0..100_000).map(Term::Var).collect());
+        let term_ref = term.to_term_ref(scope).unwrap();
+        assert_eq!(term_ref.to_term().unwrap(), term);
+    });
+    sicstus_rs::println!("test_term_many_variables, Ok");
+}
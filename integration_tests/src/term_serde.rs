@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sicstus_rs::{
+    from_term, to_term, to_term_with_config, Config, Predicate, StringStyle, TermRef, TermScope,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Circle(f64),
+    Rectangle {
+        corner: Point,
+        width: f64,
+        height: f64,
+    },
+    Empty,
+}
+
+pub fn test_serde() {
+    test_from_term();
+    test_to_term();
+    test_roundtrip();
+    test_strings_as_codes();
+    test_serde_integers();
+}
+
+fn shapes() -> Vec<Shape> {
+    vec![
+        Shape::Circle(1.5),
+        Shape::Rectangle {
+            corner: Point { x: 0, y: 0 },
+            width: 2.0,
+            height: 3.0,
+        },
+        Shape::Empty,
+    ]
+}
+
+fn test_from_term() {
+    TermScope::run(|scope| {
+        let pred = Predicate::new("user", "test_shapes", 1).expect("test_shapes/1 should exist");
+        let args = pred
+            .call(&[TermRef::new_variable(scope)])
+            .unwrap()
+            .expect("test_shapes/1 should have a solution");
+        let parsed: Vec<Shape> = from_term(&args[0]).unwrap();
+        assert_eq!(parsed, shapes());
+    });
+    sicstus_rs::println!("test_from_term, Ok");
+}
+
+fn test_to_term() {
+    let pred = Predicate::new("user", "test_shapes", 1).expect("test_shapes/1 should exist");
+    TermScope::run(|scope| {
+        let term = to_term(scope, &shapes()).unwrap();
+        assert!(pred.call_once(&[term]).unwrap());
+    });
+    sicstus_rs::println!("test_to_term, Ok");
+}
+
+fn test_roundtrip() {
+    let mut map = BTreeMap::new();
+    map.insert(String::from("a"), Some(1));
+    map.insert(String::from("b"), None);
+    TermScope::run(|scope| {
+        let term = to_term(scope, &map).unwrap();
+        assert!(term.is_list());
+        let parsed: BTreeMap<String, Option<i32>> = from_term(&term).unwrap();
+        assert_eq!(parsed, map);
+    });
+    sicstus_rs::println!("test_roundtrip, Ok");
+}
+
+fn test_strings_as_codes() {
+    TermScope::run(|scope| {
+        let config = Config {
+            strings: StringStyle::Codes,
+        };
+        let term = to_term_with_config(scope, "abc", config).unwrap();
+        assert!(term.is_list());
+        let parsed: String = from_term(&term).unwrap();
+        assert_eq!(parsed, "abc");
+        // The empty string is the empty code list [].
+        let term = to_term_with_config(scope, "", config).unwrap();
+        let parsed: String = from_term(&term).unwrap();
+        assert_eq!(parsed, "");
+    });
+    sicstus_rs::println!("test_strings_as_codes, Ok");
+}
+
+fn test_serde_integers() {
+    TermScope::run(|scope| {
+        // Serde agrees with ToTerm and FromTerm on which integers fit.
+        let term = to_term(scope, &u64::MAX).unwrap();
+        assert_eq!(u64::try_from(&term).unwrap(), u64::MAX);
+        assert_eq!(from_term::<u64>(&term).unwrap(), u64::MAX);
+        assert!(from_term::<i64>(&term).is_err());
+        let term = to_term(scope, &i128::MIN).unwrap();
+        assert_eq!(from_term::<i128>(&term).unwrap(), i128::MIN);
+        let term = to_term(scope, &u128::MAX).unwrap();
+        assert_eq!(from_term::<u128>(&term).unwrap(), u128::MAX);
+        assert!(from_term::<u8>(&TermRef::new_integer(scope, 256)).is_err());
+        assert_eq!(from_term::<u8>(&TermRef::new_integer(scope, 255)).unwrap(), 255);
+        assert!(from_term::<u32>(&TermRef::new_integer(scope, -1)).is_err());
+    });
+    sicstus_rs::println!("test_serde_integers, Ok");
+}
@@ -0,0 +1,104 @@
+use sicstus_rs::{Error, TermRef, TermScope, TermType, TermView, ToTerm};
+
+pub fn test_view() {
+    test_term_type();
+    test_view_match();
+    test_args();
+    test_expect_functor();
+}
+
+fn test_term_type() {
+    TermScope::run(|scope| {
+        let cases = [
+            ("_", TermType::Variable),
+            ("42", TermType::Integer),
+            ("123456789012345678901234567890", TermType::Integer),
+            ("foo", TermType::Atom),
+            ("[]", TermType::Atom),
+            ("1.5", TermType::Float),
+            ("f(x)", TermType::Compound),
+            ("[1]", TermType::Compound),
+        ];
+        for (text, expected) in cases {
+            let (term, _) = TermRef::parse(scope, text).unwrap();
+            assert_eq!(term.term_type().unwrap(), expected, "{}", text);
+        }
+    });
+    sicstus_rs::println!("test_term_type, Ok");
+}
+
+/// Sum the numbers in a term, the way a foreign predicate would walk its input.
+fn sum(term: &TermRef) -> f64 {
+    match term.view().unwrap() {
+        TermView::Integer(i) => i as f64,
+        TermView::Float(f) => f,
+        TermView::Compound { args, .. } => args.map(|arg| sum(&arg)).sum(),
+        TermView::Var | TermView::Atom(_) | TermView::BigInteger => 0.0,
+    }
+}
+
+fn test_view_match() {
+    TermScope::run(|scope| {
+        let (term, _) = TermRef::parse(scope, "point(1, 2.5, label(X, 3), [4, 5])").unwrap();
+        assert_eq!(sum(&term), 15.5);
+
+        match term.view().unwrap() {
+            TermView::Compound { name, arity, args } => {
+                assert_eq!(name.as_str(), "point");
+                assert_eq!(arity, 4);
+                assert_eq!(args.len(), 4);
+            }
+            other => panic!("expected a compound term, got {:?}", other),
+        }
+        let atom = "hello".to_term(scope).unwrap();
+        assert!(matches!(atom.view().unwrap(), TermView::Atom(a) if a.as_str() == "hello"));
+        let big = u128::MAX.to_term(scope).unwrap();
+        assert!(matches!(big.view().unwrap(), TermView::BigInteger));
+        assert!(matches!(
+            TermRef::new_variable(scope).view().unwrap(),
+            TermView::Var
+        ));
+    });
+    sicstus_rs::println!("test_view_match, Ok");
+}
+
+fn test_args() {
+    TermScope::run(|scope| {
+        let (term, _) = TermRef::parse(scope, "point(1, 2.5, label)").unwrap();
+        let args: Vec<TermRef> = term.args().unwrap().collect();
+        assert_eq!(args.len(), 3);
+        assert_eq!(term.arg::<i64>(1).unwrap(), 1);
+        assert_eq!(term.arg::<f64>(2).unwrap(), 2.5);
+        assert_eq!(term.arg::<String>(3).unwrap(), "label");
+        assert!(matches!(
+            term.get_arg(0),
+            Err(Error::ArgumentOutOfRange { index: 0, arity: 3 })
+        ));
+        assert!(matches!(
+            term.get_arg(4),
+            Err(Error::ArgumentOutOfRange { index: 4, arity: 3 })
+        ));
+        assert!(matches!(term.arg::<i64>(3), Err(Error::TypeError { .. })));
+
+        let atom = "foo".to_term(scope).unwrap();
+        assert_eq!(atom.args().unwrap().len(), 0);
+        assert!(matches!(atom.get_arg(1), Err(Error::TypeError { .. })));
+        assert!(TermRef::new_integer(scope, 1).args().is_err());
+    });
+    sicstus_rs::println!("test_args, Ok");
+}
+
+fn test_expect_functor() {
+    TermScope::run(|scope| {
+        let (term, _) = TermRef::parse(scope, "point(1, 2)").unwrap();
+        let [x, y]: [TermRef; 2] = term.expect_functor("point", 2).unwrap().try_into().unwrap();
+        assert_eq!(x.get_integer().unwrap(), 1);
+        assert_eq!(y.get_integer().unwrap(), 2);
+        assert!(matches!(
+            term.expect_functor("point", 3),
+            Err(Error::TypeError { expected, .. }) if expected == "point/3"
+        ));
+        assert!(term.expect_functor("line", 2).is_err());
+    });
+    sicstus_rs::println!("test_expect_functor, Ok");
+}
@@ -0,0 +1,44 @@
+use sicstus_rs::{TermRef, TermScope, WriteOptions};
+
+pub fn test_write() {
+    test_write_canonical();
+    test_write_quoted();
+    test_write_with_options();
+    test_display();
+}
+
+fn term(scope: TermScope) -> TermRef {
+    TermRef::from_prolog(scope, "f('A', 1+2, [a, b])").unwrap()
+}
+
+fn test_write_canonical() {
+    TermScope::run(|scope| {
+        assert_eq!(term(scope).to_string_canonical().unwrap(), "f('A',+(1,2),[a,b])");
+    });
+    sicstus_rs::println!("test_write_canonical, Ok");
+}
+
+fn test_write_quoted() {
+    TermScope::run(|scope| {
+        assert_eq!(term(scope).to_string_quoted().unwrap(), "f('A',1+2,[a,b])");
+    });
+    sicstus_rs::println!("test_write_quoted, Ok");
+}
+
+fn test_write_with_options() {
+    let options = WriteOptions::new().ignore_ops(true);
+    TermScope::run(|scope| {
+        assert_eq!(
+            term(scope).to_string_with_options(&options).unwrap(),
+            "f(A,+(1,2),[a,b])"
+        );
+    });
+    sicstus_rs::println!("test_write_with_options, Ok");
+}
+
+fn test_display() {
+    TermScope::run(|scope| {
+        assert_eq!(format!("{}", term(scope)), "f('A',1+2,[a,b])");
+    });
+    sicstus_rs::println!("test_display, Ok");
+}
@@ -0,0 +1,50 @@
+//! A Rust program that starts the SICStus runtime itself, instead of being loaded into Prolog.
+//!
+//! Run it with `cargo test --test embed`. It has its own `main`, since the runtime can only be started once per
+//! process and must be used from the thread that started it.
+
+use sicstus_rs::{sys, Predicate, Runtime, RuntimeOptions, TermRef, TermScope, ToTerm};
+
+const ARGS: [&str; 3] = ["embed", "--name", "value"];
+
+fn main() {
+    let runtime = Runtime::init(RuntimeOptions::new().args(ARGS)).unwrap();
+    assert!(sys::sp_is_initialized());
+    println!("test_init, Ok");
+
+    test_argv();
+    test_query();
+
+    drop(runtime);
+    assert!(!sys::sp_is_initialized());
+    println!("test_deinitialize, Ok");
+}
+
+fn test_argv() {
+    // Like in C, the first argument is the program name, which is not part of the flag.
+    let pred = Predicate::new("prolog", "prolog_flag", 2).unwrap();
+    TermScope::run(|scope| {
+        let solution = pred
+            .call(&["argv".to_term(scope).unwrap(), TermRef::new_variable(scope)])
+            .unwrap()
+            .unwrap();
+        let argv = Vec::<String>::try_from(&solution[1]).unwrap();
+        assert_eq!(argv, &ARGS[1..]);
+    });
+    println!("test_argv, Ok");
+}
+
+fn test_query() {
+    let pred = Predicate::new("prolog", "atom_length", 2).unwrap();
+    TermScope::run(|scope| {
+        let solution = pred
+            .call(&[
+                "embedded".to_term(scope).unwrap(),
+                TermRef::new_variable(scope),
+            ])
+            .unwrap()
+            .unwrap();
+        assert_eq!(solution[1].get_integer().unwrap(), 8);
+    });
+    println!("test_query, Ok");
+}
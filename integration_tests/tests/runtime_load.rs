@@ -0,0 +1,116 @@
+//! Loading Prolog code into a runtime started by Rust.
+//!
+//! Run it with `cargo test --test runtime_load`. Like `embed`, it has its own `main` because it starts the runtime.
+
+use std::{env, fs, path::PathBuf};
+
+use sicstus_rs::{LoadOptions, Predicate, Runtime, RuntimeOptions, TermRef, TermScope, ToTerm};
+
+fn main() {
+    let runtime = Runtime::init(RuntimeOptions::new().arg("runtime_load")).unwrap();
+    test_load(&runtime);
+    test_load_path(&runtime);
+    test_load_files_module(&runtime);
+    test_load_files_condition(&runtime);
+    test_consult_str(&runtime);
+    test_consult_str_with(&runtime);
+    test_restore(&runtime);
+}
+
+/// A file in the temporary directory with the given Prolog text.
+fn temp_file(name: &str, text: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("sicstus_rs_{}_{}", std::process::id(), name));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+/// The integer `X` of the first solution of `module:name(X)`.
+fn fact(module: &str, name: &str) -> i64 {
+    let pred = Predicate::new(module, name, 1).unwrap();
+    TermScope::run(|scope| {
+        let solution = pred.call(&[TermRef::new_variable(scope)]).unwrap().unwrap();
+        solution[0].get_integer().unwrap()
+    })
+}
+
+fn test_load(runtime: &Runtime) {
+    let path = temp_file("load.pl", "loaded_str(1).\n");
+    runtime.load(path.to_str().unwrap()).unwrap();
+    assert_eq!(fact("user", "loaded_str"), 1);
+    fs::remove_file(path).unwrap();
+    println!("test_load, Ok");
+}
+
+fn test_load_path(runtime: &Runtime) {
+    let path = temp_file("load_path.pl", "loaded_path(2).\n");
+    runtime.load(&path).unwrap();
+    assert_eq!(fact("user", "loaded_path"), 2);
+    fs::remove_file(path).unwrap();
+    println!("test_load_path, Ok");
+}
+
+fn test_load_files_module(runtime: &Runtime) {
+    let path = temp_file("module.pl", "loaded_module(3).\n");
+    let options = LoadOptions::new()
+        .module("load_test")
+        .compilation_mode("consult");
+    runtime.load_files(&path, &options).unwrap();
+    assert_eq!(fact("load_test", "loaded_module"), 3);
+    assert!(Predicate::new("user", "loaded_module", 1).is_err());
+    fs::remove_file(path).unwrap();
+    println!("test_load_files_module, Ok");
+}
+
+fn test_load_files_condition(runtime: &Runtime) {
+    let path = temp_file("condition.pl", "loaded_condition(4).\n");
+    runtime.load_files(&path, &LoadOptions::new()).unwrap();
+    fs::write(&path, "loaded_condition(5).\n").unwrap();
+    // The file is loaded already, so it is not loaded again even though it changed.
+    let options = LoadOptions::new().condition("not_loaded");
+    runtime.load_files(&path, &options).unwrap();
+    assert_eq!(fact("user", "loaded_condition"), 4);
+    runtime
+        .load_files(&path, &LoadOptions::new().condition("true"))
+        .unwrap();
+    assert_eq!(fact("user", "loaded_condition"), 5);
+    fs::remove_file(path).unwrap();
+    println!("test_load_files_condition, Ok");
+}
+
+fn test_consult_str(runtime: &Runtime) {
+    runtime
+        .consult_str("consulted(6).\n:- assertz(consulted_directive(7)).\n")
+        .unwrap();
+    assert_eq!(fact("user", "consulted"), 6);
+    assert_eq!(fact("user", "consulted_directive"), 7);
+    println!("test_consult_str, Ok");
+}
+
+fn test_consult_str_with(runtime: &Runtime) {
+    let options = LoadOptions::new().module("consult_test");
+    runtime
+        .consult_str_with("consulted_module(8).\n", &options)
+        .unwrap();
+    assert_eq!(fact("consult_test", "consulted_module"), 8);
+    println!("test_consult_str_with, Ok");
+}
+
+fn test_restore(runtime: &Runtime) {
+    let saved_state = env::temp_dir().join(format!("sicstus_rs_{}_state.sav", std::process::id()));
+    runtime.consult_str("saved(9).\n").unwrap();
+    let save_program = Predicate::new("prolog", "save_program", 1).unwrap();
+    TermScope::run(|scope| {
+        let file = saved_state.to_str().unwrap().to_term(scope).unwrap();
+        assert!(save_program.call_once(&[file]).unwrap());
+    });
+
+    runtime.consult_str("not_saved(10).\n").unwrap();
+    runtime.restore(&saved_state).unwrap();
+    assert_eq!(fact("user", "saved"), 9);
+    assert!(Predicate::new("user", "not_saved", 1).is_err());
+    // Text is loaded through codesio, which has to be loaded again after the restore.
+    runtime.consult_str("restored(11).\n").unwrap();
+    assert_eq!(fact("user", "restored"), 11);
+    fs::remove_file(saved_state).unwrap();
+    println!("test_restore, Ok");
+}
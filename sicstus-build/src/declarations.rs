@@ -0,0 +1,347 @@
+//! The `foreign/3` and `foreign_resource/2` declarations of functions with the `#[sicstus_rs::foreign]` attribute.
+//!
+//! [ForeignResource](crate::ForeignResource) scans the sources of the crate for them, since it runs before the
+//! crate is compiled. It is the only writer of the generated file, so the attribute itself only parses its
+//! arguments with [ForeignAttrs].
+
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    meta::ParseNestedMeta,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Ident, Item, LitStr, Meta, Token,
+};
+
+use crate::Error;
+
+/// The Prolog types of foreign arguments, see "Conversion Declarations" in the SICStus manual.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Integer,
+    Float,
+    Atom,
+    String,
+    Term,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Integer => "integer",
+            Kind::Float => "float",
+            Kind::Atom => "atom",
+            Kind::String => "string",
+            Kind::Term => "term",
+        }
+    }
+}
+
+/// One argument of `args(...)`, like `+integer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArgSpec {
+    pub output: bool,
+    pub kind: Kind,
+}
+
+impl Parse for ArgSpec {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        let output = if lookahead.peek(Token![+]) {
+            input.parse::<Token![+]>()?;
+            false
+        } else if lookahead.peek(Token![-]) {
+            input.parse::<Token![-]>()?;
+            true
+        } else {
+            return Err(lookahead.error());
+        };
+        let ident: Ident = input.parse()?;
+        let kind = match ident.to_string().as_str() {
+            "integer" => Kind::Integer,
+            "float" => Kind::Float,
+            "atom" => Kind::Atom,
+            "string" => Kind::String,
+            "term" => Kind::Term,
+            _ => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "expected integer, float, atom, string or term",
+                ))
+            }
+        };
+        Ok(ArgSpec { output, kind })
+    }
+}
+
+impl ArgSpec {
+    fn prolog(&self) -> String {
+        format!(
+            "{}{}",
+            if self.output { '-' } else { '+' },
+            self.kind.name()
+        )
+    }
+}
+
+/// The arguments of `#[foreign(...)]`.
+#[derive(Debug, Default)]
+pub struct ForeignAttrs {
+    pub name: Option<String>,
+    pub c_name: Option<String>,
+    pub resource: Option<String>,
+    pub args: Vec<ArgSpec>,
+}
+
+impl ForeignAttrs {
+    /// Parse one argument of the attribute, for use with [syn::meta::parser].
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("c_name") {
+            let c_name: LitStr = meta.value()?.parse()?;
+            if !is_c_identifier(&c_name.value()) {
+                return Err(syn::Error::new(c_name.span(), "expected a C identifier"));
+            }
+            self.c_name = Some(c_name.value());
+        } else if meta.path.is_ident("resource") {
+            self.resource = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("args") {
+            let content;
+            parenthesized!(content in meta.input);
+            self.args = Punctuated::<ArgSpec, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect();
+        } else {
+            return Err(meta.error("unsupported foreign attribute"));
+        }
+        Ok(())
+    }
+
+    /// Parse `attr` if it is `#[foreign]` or `#[sicstus_rs::foreign]`, with or without arguments.
+    fn from_attribute(attr: &Attribute) -> syn::Result<Option<Self>> {
+        let path = attr.path();
+        let foreign = path.is_ident("foreign")
+            || path.segments.len() == 2
+                && path.segments[0].ident == "sicstus_rs"
+                && path.segments[1].ident == "foreign";
+        if !foreign {
+            return Ok(None);
+        }
+        let mut attrs = ForeignAttrs::default();
+        if let Meta::List(_) = attr.meta {
+            attr.parse_nested_meta(|meta| attrs.parse(meta))?;
+        }
+        Ok(Some(attrs))
+    }
+}
+
+/// The declaration of one foreign function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Declaration {
+    pub resource: String,
+    pub c_name: String,
+    /// The `foreign/3` fact.
+    pub fact: String,
+}
+
+impl Declaration {
+    /// The declaration of the function `ident`. `resource` is used unless the attribute names one.
+    pub fn new(attrs: &ForeignAttrs, ident: &Ident, resource: &str) -> Self {
+        let c_name = attrs.c_name.clone().unwrap_or_else(|| ident.to_string());
+        let name = attrs.name.clone().unwrap_or_else(|| ident.to_string());
+        let mut predicate = quote_atom(&name);
+        if !attrs.args.is_empty() {
+            let args: Vec<String> = attrs.args.iter().map(ArgSpec::prolog).collect();
+            predicate = format!("{}({})", predicate, args.join(", "));
+        }
+        Declaration {
+            resource: attrs
+                .resource
+                .clone()
+                .unwrap_or_else(|| String::from(resource)),
+            fact: format!("foreign({}, c, {}).", quote_atom(&c_name), predicate),
+            c_name,
+        }
+    }
+}
+
+/// The generated file of `resource` in `dir`.
+///
+/// It is `$SICSTUS_FOREIGN_PL` if set, and `<resource>_foreign.pl` in `dir` otherwise.
+pub fn declarations_file(dir: &Path, resource: &str) -> PathBuf {
+    match env::var_os("SICSTUS_FOREIGN_PL") {
+        Some(path) => PathBuf::from(path),
+        None => dir.join(format!("{}_foreign.pl", resource)),
+    }
+}
+
+/// Write the generated file of `resource` in `dir` with `declarations`, and return its path.
+pub fn write(dir: &Path, resource: &str, declarations: &[Declaration]) -> Result<PathBuf, Error> {
+    let path = declarations_file(dir, resource);
+    let facts = declarations
+        .iter()
+        .map(|declaration| (quote_atom(&declaration.c_name), declaration.fact.clone()))
+        .collect();
+    update(&path, &contents(resource, &facts))?;
+    Ok(path)
+}
+
+/// Write the file unless it already has `contents`, so its timestamp only changes with the declarations.
+fn update(path: &Path, contents: &str) -> io::Result<()> {
+    if fs::read_to_string(path).ok().as_deref() != Some(contents) {
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+/// The generated file, with the facts by their quoted C name.
+fn contents(resource: &str, facts: &BTreeMap<String, String>) -> String {
+    let names: Vec<&str> = facts.keys().map(String::as_str).collect();
+    let mut contents = String::from("% Generated by #[sicstus_rs::foreign], do not edit.\n");
+    contents.push_str(&format!(
+        "foreign_resource({}, [{}]).\n",
+        quote_atom(resource),
+        names.join(", ")
+    ));
+    for fact in facts.values() {
+        contents.push_str(fact);
+        contents.push('\n');
+    }
+    contents
+}
+
+/// The declarations of all foreign functions of `resource` in the Rust files below `dir`.
+pub fn scan(dir: &Path, resource: &str) -> Result<Vec<Declaration>, Error> {
+    let mut declarations = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            declarations.extend(scan(&path, resource)?);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            let file =
+                syn::parse_file(&fs::read_to_string(&path)?).map_err(|error| Error::Parse {
+                    path: path.clone(),
+                    error,
+                })?;
+            scan_items(&file.items, resource, &mut declarations)
+                .map_err(|error| Error::Parse { path, error })?;
+        }
+    }
+    Ok(declarations)
+}
+
+fn scan_items(
+    items: &[Item],
+    resource: &str,
+    declarations: &mut Vec<Declaration>,
+) -> syn::Result<()> {
+    for item in items {
+        match item {
+            Item::Fn(function) => {
+                for attr in &function.attrs {
+                    if let Some(attrs) = ForeignAttrs::from_attribute(attr)? {
+                        let declaration = Declaration::new(&attrs, &function.sig.ident, resource);
+                        if declaration.resource == resource {
+                            declarations.push(declaration);
+                        }
+                    }
+                }
+            }
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    scan_items(items, resource, declarations)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Write `name` as a Prolog atom, quoted unless it is a plain identifier.
+fn quote_atom(name: &str) -> String {
+    let mut chars = name.chars();
+    let plain = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        String::from(name)
+    } else {
+        format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+fn is_c_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn declarations(file: syn::File) -> Vec<Declaration> {
+        let mut declarations = Vec::new();
+        scan_items(&file.items, "lib", &mut declarations).unwrap();
+        declarations
+    }
+
+    #[test]
+    fn test_scan() {
+        let file: syn::File = parse_quote! {
+            #[sicstus_rs::foreign(name = "add", args(+integer, +integer, -integer))]
+            fn rust_add(a: i64, b: i64) -> i64 {
+                a + b
+            }
+
+            mod inner {
+                #[foreign(name = "Main")]
+                fn main() {}
+            }
+
+            #[foreign(resource = "other")]
+            fn elsewhere() {}
+
+            #[other::foreign]
+            fn unrelated() {}
+        };
+        let facts: Vec<String> = declarations(file).into_iter().map(|d| d.fact).collect();
+        assert_eq!(
+            facts,
+            vec![
+                "foreign(rust_add, c, add(+integer, +integer, -integer)).",
+                "foreign(main, c, 'Main').",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contents() {
+        let file: syn::File = parse_quote! {
+            #[foreign(args(-atom))]
+            fn b() -> String {}
+            #[foreign(args(+term))]
+            fn a(term: TermRef) {}
+        };
+        let facts = declarations(file)
+            .into_iter()
+            .map(|d| (quote_atom(&d.c_name), d.fact))
+            .collect();
+        assert_eq!(
+            contents("lib", &facts),
+            "% Generated by #[sicstus_rs::foreign], do not edit.\n\
+             foreign_resource(lib, [a, b]).\n\
+             foreign(a, c, a(+term)).\n\
+             foreign(b, c, b(-atom)).\n"
+        );
+    }
+}
@@ -0,0 +1,435 @@
+//! Build SICStus Prolog foreign resources written in Rust from `build.rs`.
+//!
+//! A foreign resource is a crate with `crate-type = ["cdylib"]` whose foreign functions have the
+//! `#[sicstus_rs::foreign]` attribute. Its build script is
+//!
+//! ```no_run
+//! // build.rs
+//! sicstus_build::ForeignResource::new().build();
+//! ```
+//!
+//! and `cargo build` then writes the resource `<name>.so` and the generated `<name>_foreign.pl` to `OUT_DIR`, or
+//! to the directory given with [ForeignResource::out_dir]. Their paths are passed to the crate in the environment
+//! variables `SICSTUS_RESOURCE` and `SICSTUS_RESOURCE_DECLARATIONS`, e.g. for `env!("SICSTUS_RESOURCE")`.
+//!
+//! The resource finds the library of the crate in its own directory, so `load_foreign_resource/1` can load it
+//! once both are in the same place, e.g. after copying the resource next to the library in `target/debug`.
+//!
+//! The build script
+//!
+//! 1. locates SICStus, see [Sicstus::locate],
+//! 2. writes the `foreign/3` and `foreign_resource/2` declarations of the foreign functions to
+//!    `<name>_foreign.pl`, see [declarations],
+//! 3. runs `splfr` on them to generate the glue code and compiles it with the [cc] crate,
+//! 4. links the glue into `<name>.so`, which loads the library of the crate when SICStus loads it.
+
+pub mod declarations;
+
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    /// SICStus was not found in `SICSTUSDIR` or on the `PATH`.
+    NotFound,
+    /// The version printed by `sicstus --version` could not be read.
+    UnknownVersion(String),
+    /// An environment variable that cargo sets for build scripts is missing.
+    NotInBuildScript(&'static str),
+    /// Foreign resources can only be linked for this target OS.
+    UnsupportedTarget(String),
+    /// A Rust file could not be parsed.
+    Parse {
+        path: PathBuf,
+        error: syn::Error,
+    },
+    /// A program like `splfr` or the linker failed.
+    Command {
+        command: String,
+        output: String,
+    },
+    Compile(cc::Error),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound => f.write_str(
+                "SICStus not found, set SICSTUSDIR to the SICStus installation or add sicstus to the PATH",
+            ),
+            Error::UnknownVersion(output) => {
+                write!(f, "unknown SICStus version in {:?}", output)
+            }
+            Error::NotInBuildScript(variable) => {
+                write!(f, "{} is not set, run this from a build script", variable)
+            }
+            Error::UnsupportedTarget(os) => {
+                write!(f, "can not link foreign resources for {}", os)
+            }
+            Error::Parse { path, error } => write!(f, "{}: {}", path.display(), error),
+            Error::Command { command, output } => write!(f, "{} failed:\n{}", command, output),
+            Error::Compile(e) => write!(f, "compiling the glue code failed: {}", e),
+            Error::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<cc::Error> for Error {
+    fn from(e: cc::Error) -> Self {
+        Error::Compile(e)
+    }
+}
+
+/// A SICStus version, like `4.9.0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// The version as `SICSTUS_TARGET_VERSION` expects it, e.g. `40900`.
+    pub fn target_version(&self) -> u32 {
+        self.major * 10000 + self.minor * 100 + self.patch
+    }
+
+    /// Find the first version number in `text`.
+    fn find(text: &str) -> Option<Self> {
+        text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .find_map(|word| {
+                let mut parts = word.split('.').map(|part| part.parse().ok());
+                match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                    (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => {
+                        Some(Version {
+                            major,
+                            minor,
+                            patch,
+                        })
+                    }
+                    _ => None,
+                }
+            })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A SICStus installation.
+#[derive(Clone, Debug)]
+pub struct Sicstus {
+    root: PathBuf,
+    version: Version,
+}
+
+impl Sicstus {
+    /// Find SICStus in the `SICSTUSDIR` environment variable, or else the installation of `sicstus` on the `PATH`.
+    pub fn locate() -> Result<Self, Error> {
+        println!("cargo:rerun-if-env-changed=SICSTUSDIR");
+        let root = match env::var_os("SICSTUSDIR") {
+            Some(root) => PathBuf::from(root),
+            None => env::var_os("PATH")
+                .and_then(|path| {
+                    env::split_paths(&path)
+                        .map(|dir| dir.join("sicstus"))
+                        .find(|sicstus| sicstus.is_file())
+                })
+                // The binary may be a link into the installation, whose root is the parent of bin.
+                .and_then(|sicstus| fs::canonicalize(sicstus).ok())
+                .and_then(|sicstus| Some(sicstus.parent()?.parent()?.to_path_buf()))
+                .ok_or(Error::NotFound)?,
+        };
+        Sicstus::new(root)
+    }
+
+    /// The installation in `root`, which contains `bin/sicstus`.
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        let sicstus = root.join("bin").join("sicstus");
+        let output = Command::new(&sicstus)
+            .arg("--version")
+            .output()
+            .map_err(|_| Error::NotFound)?;
+        // sicstus prints its banner on stderr.
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let version = Version::find(&text).ok_or(Error::UnknownVersion(text))?;
+        Ok(Sicstus { root, version })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn bin(&self, program: &str) -> PathBuf {
+        self.root.join("bin").join(program)
+    }
+
+    pub fn include_dir(&self) -> PathBuf {
+        self.root.join("include")
+    }
+
+    pub fn lib_dir(&self) -> PathBuf {
+        self.root.join("lib")
+    }
+
+    /// The name of the runtime library, e.g. `sprt4-9-0`.
+    pub fn runtime_library(&self) -> String {
+        format!(
+            "sprt{}-{}-{}",
+            self.version.major, self.version.minor, self.version.patch
+        )
+    }
+
+    /// Tell cargo to link a program that embeds SICStus against the runtime library, and to find it at run time.
+    ///
+    /// sicstus-sys links the runtime library already, but does not set the run path of the program.
+    pub fn link_runtime(&self) {
+        let lib_dir = self.lib_dir();
+        println!("cargo:rustc-link-search=native={}", lib_dir.display());
+        println!("cargo:rustc-link-lib=dylib={}", self.runtime_library());
+        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir.display());
+    }
+}
+
+/// Builds the foreign resource of the crate, see the [crate] documentation.
+#[derive(Debug, Default)]
+pub struct ForeignResource {
+    name: Option<String>,
+    prolog_file: Option<PathBuf>,
+    sicstus: Option<Sicstus>,
+    out_dir: Option<PathBuf>,
+}
+
+impl ForeignResource {
+    pub fn new() -> Self {
+        ForeignResource::default()
+    }
+
+    /// The name of the resource. Defaults to the name of the crate.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    /// The Prolog file passed to `splfr`, relative to the crate directory.
+    ///
+    /// It must include the generated `<name>_foreign.pl` from the [ForeignResource::out_dir], and can declare
+    /// more foreign functions. Defaults to the generated file itself.
+    pub fn prolog_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.prolog_file = Some(path.into());
+        self
+    }
+
+    /// The SICStus installation to build against, instead of [Sicstus::locate].
+    pub fn sicstus(mut self, sicstus: Sicstus) -> Self {
+        self.sicstus = Some(sicstus);
+        self
+    }
+
+    /// The directory of the resource and the generated `<name>_foreign.pl`, relative to the crate directory.
+    /// Defaults to `OUT_DIR`.
+    pub fn out_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(path.into());
+        self
+    }
+
+    /// Build the resource and return its path.
+    ///
+    /// # Panics
+    /// Panics with the error if the build fails, which fails the build script. Use [ForeignResource::try_build]
+    /// to handle the error.
+    pub fn build(&self) -> PathBuf {
+        match self.try_build() {
+            Ok(path) => path,
+            Err(e) => panic!("building the foreign resource failed: {}", e),
+        }
+    }
+
+    pub fn try_build(&self) -> Result<PathBuf, Error> {
+        let os = env_var("CARGO_CFG_TARGET_OS")?;
+        if os != "linux" {
+            return Err(Error::UnsupportedTarget(os));
+        }
+        let sicstus = match &self.sicstus {
+            Some(sicstus) => sicstus.clone(),
+            None => Sicstus::locate()?,
+        };
+        let manifest_dir = PathBuf::from(env_var("CARGO_MANIFEST_DIR")?);
+        let out_dir = PathBuf::from(env_var("OUT_DIR")?);
+        let library = env_var("CARGO_PKG_NAME")?.replace('-', "_");
+        let name = self.name.clone().unwrap_or_else(|| library.clone());
+
+        let sources = manifest_dir.join("src");
+        println!("cargo:rerun-if-changed={}", sources.display());
+        println!("cargo:rerun-if-env-changed=SICSTUS_FOREIGN_PL");
+        let resource_dir = match &self.out_dir {
+            Some(path) => manifest_dir.join(path),
+            None => out_dir.clone(),
+        };
+        fs::create_dir_all(&resource_dir)?;
+        let declarations = declarations::scan(&sources, &name)?;
+        let generated = declarations::write(&resource_dir, &name, &declarations)?;
+        let prolog_file = match &self.prolog_file {
+            Some(path) => {
+                let path = manifest_dir.join(path);
+                println!("cargo:rerun-if-changed={}", path.display());
+                path
+            }
+            None => generated.clone(),
+        };
+
+        // splfr writes the glue code to the current directory.
+        let mut splfr = Command::new(sicstus.bin("splfr"));
+        splfr
+            .current_dir(&out_dir)
+            .arg("--nocompile")
+            .arg(format!("--resource={}", name))
+            .arg(&prolog_file);
+        run(&mut splfr)?;
+
+        let glue = cc::Build::new()
+            .file(out_dir.join(format!("{}_glue.c", name)))
+            .include(sicstus.include_dir())
+            .include(&out_dir)
+            .define("SPDLL", None)
+            .define("_REENTRANT", None)
+            .define("SP_RESNAME", resource_macro_name(&name).as_str())
+            .define(
+                "SICSTUS_TARGET_VERSION",
+                sicstus.version().target_version().to_string().as_str(),
+            )
+            .pic(true)
+            .cargo_metadata(false)
+            .try_compile_intermediates()?;
+
+        // The glue calls the foreign functions in the library of the crate, which is linked after the build script.
+        // Linking against an empty library of the same name makes the loader look for it next to the resource.
+        let stub_dir = out_dir.join("stub");
+        fs::create_dir_all(&stub_dir)?;
+        let stub_source = stub_dir.join("stub.c");
+        fs::write(&stub_source, "")?;
+        let stub = cc::Build::new()
+            .file(&stub_source)
+            .pic(true)
+            .cargo_metadata(false)
+            .try_compile_intermediates()?;
+        let stub_library = format!("lib{}.so", library);
+        let mut link = linker()?;
+        link.args(&stub)
+            .arg(format!("-Wl,-soname,{}", stub_library))
+            .arg("-o")
+            .arg(stub_dir.join(&stub_library));
+        run(&mut link)?;
+
+        let resource = resource_dir.join(format!("{}.so", name));
+        let mut link = linker()?;
+        link.args(&glue)
+            .arg("-Wl,--no-as-needed")
+            .arg(format!("-L{}", stub_dir.display()))
+            .arg(format!("-l{}", library))
+            .arg("-Wl,-rpath,$ORIGIN")
+            .arg("-o")
+            .arg(&resource);
+        let mapfile = out_dir.join(format!("{}_glue.mapfile", name));
+        if mapfile.is_file() {
+            link.arg(format!("-Wl,--version-script={}", mapfile.display()));
+        }
+        run(&mut link)?;
+        println!("cargo:rustc-env=SICSTUS_RESOURCE={}", resource.display());
+        println!(
+            "cargo:rustc-env=SICSTUS_RESOURCE_DECLARATIONS={}",
+            generated.display()
+        );
+        Ok(resource)
+    }
+}
+
+/// The value of `SP_RESNAME`, which encodes the characters of the name that are not letters or digits,
+/// e.g. `integration_0x5Ftests` for `integration_tests`.
+fn resource_macro_name(name: &str) -> String {
+    let mut result = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            result.push(c);
+        } else {
+            result.push_str(&format!("_0x{:X}", c as u32));
+        }
+    }
+    result
+}
+
+fn env_var(name: &'static str) -> Result<String, Error> {
+    env::var(name).map_err(|_| Error::NotInBuildScript(name))
+}
+
+/// The C compiler, set up to link a shared library.
+fn linker() -> Result<Command, Error> {
+    let mut command = cc::Build::new().try_get_compiler()?.to_command();
+    command.arg("-shared");
+    Ok(command)
+}
+
+fn run(command: &mut Command) -> Result<(), Error> {
+    let output = command.output().map_err(|e| Error::Command {
+        command: format!("{:?}", command),
+        output: e.to_string(),
+    })?;
+    if !output.status.success() {
+        return Err(Error::Command {
+            command: format!("{:?}", command),
+            output: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version() {
+        let banner = "SICStus 4.9.0 (x86_64-linux-glibc2.28): Thu Dec  7 15:52:33 CET 2023";
+        let version = Version::find(banner).unwrap();
+        assert_eq!(version.to_string(), "4.9.0");
+        assert_eq!(version.target_version(), 40900);
+        assert_eq!(Version::find("x86_64-linux-glibc2.28"), None);
+    }
+
+    #[test]
+    fn test_resource_macro_name() {
+        assert_eq!(
+            resource_macro_name("integration_tests"),
+            "integration_0x5Ftests"
+        );
+        assert_eq!(resource_macro_name("clpfd"), "clpfd");
+    }
+}
@@ -0,0 +1,124 @@
+//! The `#[foreign]` attribute.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use sicstus_build::declarations::{ArgSpec, ForeignAttrs, Kind};
+use syn::{FnArg, Ident, ItemFn, Result, ReturnType, Type};
+
+/// The type of the parameter in the C function called by the glue code.
+fn c_type(arg: &ArgSpec) -> TokenStream2 {
+    match (arg.output, arg.kind) {
+        (false, Kind::Integer) => quote!(::sicstus_rs::SP_integer),
+        (false, Kind::Float) => quote!(f64),
+        (false, Kind::Atom) => quote!(::sicstus_rs::SP_atom),
+        (false, Kind::String) => quote!(*const ::core::ffi::c_char),
+        (true, Kind::Integer) => quote!(*mut ::sicstus_rs::SP_integer),
+        (true, Kind::Float) => quote!(*mut f64),
+        (true, Kind::Atom) => quote!(*mut ::sicstus_rs::SP_atom),
+        (true, Kind::String) => quote!(*mut *const ::core::ffi::c_char),
+        (_, Kind::Term) => quote!(::sicstus_rs::SP_term_ref),
+    }
+}
+
+/// The helper that converts an input parameter to a term, or stores a value in an output parameter.
+fn helper(arg: &ArgSpec) -> Ident {
+    let prefix = if arg.output { "put_" } else { "" };
+    format_ident!("{}{}", prefix, arg.kind.name())
+}
+
+pub fn expand_foreign(attrs: ForeignAttrs, function: ItemFn) -> Result<TokenStream2> {
+    let ident = &function.sig.ident;
+    let name = attrs.name.clone().unwrap_or_else(|| ident.to_string());
+    let c_name = attrs.c_name.clone().unwrap_or_else(|| ident.to_string());
+    check_signature(&attrs, &function)?;
+    let arity = attrs.args.len();
+    let mut params = Vec::new();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let output_count = attrs.args.iter().filter(|arg| arg.output).count();
+    for (i, arg) in attrs.args.iter().enumerate() {
+        let param = format_ident!("__arg{}", i);
+        let c_type = c_type(arg);
+        let helper = helper(arg);
+        params.push(quote!(#param: #c_type));
+        if arg.output {
+            let value = if output_count == 1 {
+                quote!(&result)
+            } else {
+                let index = syn::Index::from(outputs.len());
+                quote!(&result.#index)
+            };
+            outputs.push(quote!(::sicstus_rs::foreign::__private::#helper(__scope, #param, #value)?;));
+        } else {
+            inputs.push(quote!(::sicstus_rs::FromTerm::from_term(
+                &::sicstus_rs::foreign::__private::#helper(__scope, #param)?
+            )?));
+        }
+    }
+    let propagate = fallible(&function.sig.output).then(|| quote!(let result = result?;));
+    let wrapper = format_ident!("__sicstus_foreign_{}", ident);
+
+    Ok(quote! {
+        #function
+
+        #[doc(hidden)]
+        #[export_name = #c_name]
+        #[allow(clippy::let_unit_value, clippy::unused_unit)]
+        unsafe extern "C" fn #wrapper(#(#params),*) {
+            ::sicstus_rs::foreign::__private::call(#name, #arity, |__scope| -> ::core::result::Result<(), ::sicstus_rs::Error> {
+                let result = #ident(#(#inputs),*);
+                #propagate
+                #(#outputs)*
+                ::core::result::Result::Ok(())
+            })
+        }
+    })
+}
+
+fn check_signature(attrs: &ForeignAttrs, function: &ItemFn) -> Result<()> {
+    let sig = &function.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "a foreign function can not be generic or async",
+        ));
+    }
+    if let Some(FnArg::Receiver(receiver)) = sig.inputs.first() {
+        return Err(syn::Error::new_spanned(
+            receiver,
+            "a foreign function can not take self",
+        ));
+    }
+    let input_count = attrs.args.iter().filter(|arg| !arg.output).count();
+    if sig.inputs.len() != input_count {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            format!(
+                "expected one parameter for each of the {} input arguments",
+                input_count
+            ),
+        ));
+    }
+    if attrs.args.iter().any(|arg| arg.output) && matches!(sig.output, ReturnType::Default) {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "the output arguments are taken from the return value",
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the function returns a `Result`, whose error is raised as an exception.
+fn fallible(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
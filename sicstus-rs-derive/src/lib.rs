@@ -0,0 +1,431 @@
+//! Derive macros for the `ToTerm` and `FromTerm` traits of sicstus-rs, and the `foreign` attribute.
+//!
+//! Use them through the `derive` feature of sicstus-rs, which documents the supported attributes.
+
+mod foreign;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Generics, Ident, ItemFn,
+    LitInt, LitStr, Member, Result,
+};
+
+#[proc_macro_derive(ToTerm, attributes(term))]
+pub fn derive_to_term(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_term(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromTerm, attributes(term))]
+pub fn derive_from_term(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_term(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turn a Rust function into a foreign function that `splfr` can link into a foreign resource.
+///
+/// ```ignore
+/// #[sicstus_rs::foreign(name = "add", args(+integer, +integer, -integer))]
+/// fn add(a: i64, b: i64) -> Result<i64, Error> {
+///     a.checked_add(b).ok_or(Error::Conversion("integer overflow".into()))
+/// }
+/// ```
+///
+/// `args` lists the arguments of the predicate like in `foreign/3`, with the types `integer`, `float`,
+/// `atom`, `string` and `term`. The function takes one parameter for each `+` argument, converted with `FromTerm`.
+/// The value it returns, or a tuple of values if there are several, is converted with `ToTerm` and stored in
+/// the `-` arguments. If it returns a `Result`, an `Err` is raised as an exception. Panics are raised as a
+/// `system_error`.
+///
+/// The attribute also accepts `name`, the name of the predicate, `c_name`, the exported symbol, both defaulting
+/// to the name of the function, and `resource`, defaulting to the crate name.
+///
+/// The attribute only generates code. The `ForeignResource` of the sicstus-build crate finds the annotated
+/// functions from `build.rs`, writes their `foreign/3` facts and the `foreign_resource/2` fact to
+/// `<resource>_foreign.pl`, and builds the resource.
+#[proc_macro_attribute]
+pub fn foreign(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = sicstus_build::declarations::ForeignAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+    foreign::expand_foreign(attrs, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The contents of the `#[term(...)]` attributes on an item.
+#[derive(Default)]
+struct Attrs {
+    name: Option<String>,
+    arg: Option<usize>,
+    codes: bool,
+    skip: bool,
+}
+
+impl Attrs {
+    /// Parse the attributes of a struct, enum or variant if `field` is false, and of a field otherwise.
+    fn parse(attrs: &[Attribute], field: bool) -> Result<Self> {
+        let mut result = Attrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("term")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") && !field {
+                    let name: LitStr = meta.value()?.parse()?;
+                    result.name = Some(name.value());
+                } else if meta.path.is_ident("arg") && field {
+                    let arg: LitInt = meta.value()?.parse()?;
+                    result.arg = Some(arg.base10_parse()?);
+                } else if meta.path.is_ident("codes") && field {
+                    result.codes = true;
+                } else if meta.path.is_ident("atom") && field {
+                    result.codes = false;
+                } else if meta.path.is_ident("skip") && field {
+                    result.skip = true;
+                } else {
+                    return Err(meta.error("unsupported term attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(result)
+    }
+}
+
+struct Field {
+    member: Member,
+    binding: Ident,
+    attrs: Attrs,
+    /// The argument of the term the field is stored in, None if it is skipped.
+    position: Option<usize>,
+}
+
+/// The functor and the fields of a struct or variant.
+struct Shape {
+    name: String,
+    fields: Vec<Field>,
+    named: bool,
+}
+
+impl Shape {
+    fn new(ident: &Ident, attrs: &[Attribute], fields: &Fields) -> Result<Self> {
+        let name = Attrs::parse(attrs, false)?
+            .name
+            .unwrap_or_else(|| snake_case(&ident.to_string()));
+        let mut result = Vec::new();
+        for (i, field) in fields.iter().enumerate() {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(i.into()),
+            };
+            result.push(Field {
+                member,
+                binding: format_ident!("__field{}", i),
+                attrs: Attrs::parse(&field.attrs, true)?,
+                position: None,
+            });
+        }
+        assign_positions(&mut result, fields)?;
+        Ok(Shape {
+            name,
+            fields: result,
+            named: matches!(fields, Fields::Named(_)),
+        })
+    }
+
+    fn arity(&self) -> usize {
+        self.fields.iter().filter(|f| f.position.is_some()).count()
+    }
+
+    /// The fields in the order of the term arguments.
+    fn args(&self) -> Vec<&Field> {
+        let mut args: Vec<&Field> = self
+            .fields
+            .iter()
+            .filter(|f| f.position.is_some())
+            .collect();
+        args.sort_by_key(|f| f.position);
+        args
+    }
+
+    /// A pattern binding every field that is not skipped to its binding.
+    fn pattern(&self, path: TokenStream2) -> TokenStream2 {
+        let bindings = self.fields.iter().map(|f| match f.position {
+            Some(_) => {
+                let binding = &f.binding;
+                quote!(#binding)
+            }
+            None => quote!(_),
+        });
+        if self.named {
+            let members = self.fields.iter().map(|f| &f.member);
+            quote!(#path { #(#members: #bindings),* })
+        } else if self.fields.is_empty() {
+            path
+        } else {
+            quote!(#path( #(#bindings),* ))
+        }
+    }
+
+    /// Build the term from the bindings of the fields.
+    fn build_term(&self) -> TokenStream2 {
+        let name = &self.name;
+        let args = self.args().into_iter().map(|f| {
+            let binding = &f.binding;
+            if f.attrs.codes {
+                quote!(::sicstus_rs::convert::codes_term(__scope, #binding)?)
+            } else {
+                quote!(::sicstus_rs::ToTerm::to_term(#binding, __scope)?)
+            }
+        });
+        quote!(::sicstus_rs::convert::functor_term(__scope, #name, &[#(#args),*]))
+    }
+
+    /// Construct the value from the arguments of `term`, whose functor was already checked.
+    fn construct(&self, path: TokenStream2) -> TokenStream2 {
+        let values = self.fields.iter().map(|f| match f.position {
+            Some(position) => {
+                quote!(::sicstus_rs::FromTerm::from_term(&term.get_arg(#position)?)?)
+            }
+            None => quote!(::core::default::Default::default()),
+        });
+        if self.named {
+            let members = self.fields.iter().map(|f| &f.member);
+            quote!(#path { #(#members: #values),* })
+        } else if self.fields.is_empty() {
+            path
+        } else {
+            quote!(#path( #(#values),* ))
+        }
+    }
+}
+
+/// Give the fields with an explicit `arg` their position and fill the remaining positions in declaration order.
+fn assign_positions(fields: &mut [Field], span: &Fields) -> Result<()> {
+    let arity = fields.iter().filter(|f| !f.attrs.skip).count();
+    let mut taken = vec![false; arity + 1];
+    for field in fields.iter_mut().filter(|f| !f.attrs.skip) {
+        if let Some(arg) = field.attrs.arg {
+            if arg == 0 || arg > arity || taken[arg] {
+                return Err(syn::Error::new_spanned(
+                    span,
+                    format!(
+                        "argument position {} is out of range 1..={} or used twice",
+                        arg, arity
+                    ),
+                ));
+            }
+            taken[arg] = true;
+            field.position = Some(arg);
+        }
+    }
+    let mut free = (1..=arity).filter(|&i| !taken[i]);
+    for field in fields.iter_mut().filter(|f| !f.attrs.skip) {
+        if field.position.is_none() {
+            field.position = free.next();
+        }
+    }
+    Ok(())
+}
+
+/// Add `T: bound` for every type parameter.
+fn add_bounds(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    add_type_bounds(&mut generics, bound);
+    generics
+}
+
+fn add_type_bounds(generics: &mut Generics, bound: TokenStream2) {
+    let params: Vec<Ident> = generics.type_params().map(|p| p.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+}
+
+/// The shapes of all variants of an enum, or of the struct itself.
+fn shapes(input: &DeriveInput) -> Result<Vec<(Option<&Ident>, Shape)>> {
+    match &input.data {
+        Data::Struct(data) => Ok(vec![(
+            None,
+            Shape::new(&input.ident, &input.attrs, &data.fields)?,
+        )]),
+        Data::Enum(data) => {
+            let mut shapes: Vec<(Option<&Ident>, Shape)> = Vec::new();
+            for variant in &data.variants {
+                let shape = Shape::new(&variant.ident, &variant.attrs, &variant.fields)?;
+                if shapes
+                    .iter()
+                    .any(|(_, other)| other.name == shape.name && other.arity() == shape.arity())
+                {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        format!(
+                            "another variant is also converted to {}/{}",
+                            shape.name,
+                            shape.arity()
+                        ),
+                    ));
+                }
+                shapes.push((Some(&variant.ident), shape));
+            }
+            Ok(shapes)
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            input,
+            "unions can not be converted to terms",
+        )),
+    }
+}
+
+fn variant_path(variant: Option<&Ident>) -> TokenStream2 {
+    match variant {
+        Some(variant) => quote!(Self::#variant),
+        None => quote!(Self),
+    }
+}
+
+fn expand_to_term(input: &DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::sicstus_rs::ToTerm));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let arms = shapes(input)?.into_iter().map(|(variant, shape)| {
+        let pattern = shape.pattern(variant_path(variant));
+        let body = shape.build_term();
+        quote!(#pattern => #body,)
+    });
+    Ok(quote! {
+        impl #impl_generics ::sicstus_rs::ToTerm for #ident #ty_generics #where_clause {
+            fn to_term<'__s>(
+                &self,
+                __scope: ::sicstus_rs::TermScope<'__s>,
+            ) -> ::core::result::Result<::sicstus_rs::TermRef<'__s>, ::sicstus_rs::Error> {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+fn expand_from_term(input: &DeriveInput) -> Result<TokenStream2> {
+    let ident = &input.ident;
+    // The impl is generic over the lifetime of the term, which the type itself does not have.
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('__s));
+    add_type_bounds(&mut generics, quote!(::sicstus_rs::FromTerm<'__s>));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let shapes = shapes(input)?;
+    let body = match &input.data {
+        Data::Struct(_) => {
+            let shape = &shapes[0].1;
+            let name = &shape.name;
+            let arity = shape.arity();
+            let value = shape.construct(quote!(Self));
+            quote! {
+                ::sicstus_rs::convert::check_functor(term, #name, #arity)?;
+                ::core::result::Result::Ok(#value)
+            }
+        }
+        _ => {
+            let expected = snake_case(&ident.to_string());
+            let arms = shapes.iter().map(|(variant, shape)| {
+                let name = &shape.name;
+                let arity = shape.arity();
+                let value = shape.construct(variant_path(*variant));
+                quote!((#name, #arity) => ::core::result::Result::Ok(#value),)
+            });
+            quote! {
+                let (name, arity) = ::sicstus_rs::convert::functor_of(term)?;
+                match (name.as_str(), arity) {
+                    #(#arms)*
+                    _ => ::core::result::Result::Err(::sicstus_rs::convert::type_error(#expected, term)),
+                }
+            }
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::sicstus_rs::FromTerm<'__s> for #ident #ty_generics #where_clause {
+            fn from_term(term: &::sicstus_rs::TermRef<'__s>) -> ::core::result::Result<Self, ::sicstus_rs::Error> {
+                #body
+            }
+        }
+    })
+}
+
+/// Convert a Rust type name like `HttpRequest` to a Prolog atom name like `http_request`.
+/// This must agree with the conversion sicstus-rs uses for serde.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(input: DeriveInput) -> Vec<Option<usize>> {
+        let (_, shape) = shapes(&input).unwrap().remove(0);
+        shape.fields.iter().map(|f| f.position).collect()
+    }
+
+    #[test]
+    fn test_positions() {
+        let input: DeriveInput = parse_quote! {
+            struct Point {
+                x: i64,
+                #[term(arg = 1)]
+                y: i64,
+                #[term(skip)]
+                cache: u8,
+                z: i64,
+            }
+        };
+        assert_eq!(positions(input), vec![Some(2), Some(1), None, Some(3)]);
+    }
+
+    #[test]
+    fn test_invalid_position() {
+        let input: DeriveInput = parse_quote! {
+            struct Point(#[term(arg = 3)] i64, i64);
+        };
+        assert!(shapes(&input).is_err());
+    }
+
+    #[test]
+    fn test_functor_names() {
+        let input: DeriveInput = parse_quote! {
+            enum Message {
+                HttpRequest(String),
+                #[term(name = "bye")]
+                Quit,
+            }
+        };
+        let names: Vec<String> = shapes(&input)
+            .unwrap()
+            .into_iter()
+            .map(|(_, shape)| shape.name)
+            .collect();
+        assert_eq!(names, vec!["http_request", "bye"]);
+    }
+}
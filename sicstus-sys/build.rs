@@ -82,4 +82,14 @@ fn main() {
     let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     generate_bindings(sicstus_root_dir.clone(), out_path.clone());
     println!("cargo:rustc-cfg=sicstus_version=\"{}\"", sicstus_version); // for conditional compilation
+
+    // The dispatch table comes from the runtime library, e.g. libsprt4-9-0.so.
+    println!(
+        "cargo:rustc-link-search=native={}",
+        sicstus_root_dir.join("lib").to_str().unwrap()
+    );
+    println!(
+        "cargo:rustc-link-lib=dylib=sprt{}",
+        sicstus_version.replace('.', "-")
+    );
 }
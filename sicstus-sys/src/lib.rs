@@ -43,9 +43,10 @@ pub use bindings::{
     spio_t_simple_device_flush_output, spio_t_simple_device_interrupt, spio_t_simple_device_ioctl,
     spio_t_simple_device_read, spio_t_simple_device_seek, spio_t_simple_device_write, spio_t_uint8,
     spio_t_wchar, SP_CPredFun, SP_EventFun, SP_SigFun, SP_UserStreamHook, SP_UserStreamPostHook,
-    SP_atom, SP_get_dispatch_type, SP_integer, SP_mutex, SP_options, SP_pred_ref, SP_qid,
-    SP_stream, SP_term_ref, SICSTUS_API_STRUCT, SP_ERROR, SP_FAILURE, SP_SUCCESS, SP_TYPE_ATOM,
-    SP_TYPE_COMPOUND, SP_TYPE_ERROR, SP_TYPE_FLOAT, SP_TYPE_INTEGER, SP_TYPE_VARIABLE,
+    SP_atom, SP_get_dispatch_type, SP_integer, SP_mutex, SP_option, SP_option_type_system_property,
+    SP_options, SP_pred_ref, SP_qid, SP_stream, SP_term_ref, SICSTUS_API_STRUCT, SP_ERROR,
+    SP_FAILURE, SP_SUCCESS, SP_TYPE_ATOM, SP_TYPE_COMPOUND, SP_TYPE_ERROR, SP_TYPE_FLOAT,
+    SP_TYPE_INTEGER, SP_TYPE_VARIABLE,
 };
 
 // We only ever read the pointers in the dispatch table, so it is safe to share it between threads.
@@ -69,13 +70,7 @@ impl Sicstus {
 impl Sicstus {
     fn new() -> Self {
         unsafe {
-            #[cfg(sicstus_version = "4.7.1")]
-            let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40701(core::ptr::null_mut());
-            #[cfg(sicstus_version = "4.8.0")]
-            let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40800(core::ptr::null_mut());
-            #[cfg(sicstus_version = "4.9.0")]
-            let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40900(core::ptr::null_mut());
-
+            let sicstus = dispatch();
             let dt = (*sicstus).dispatch_API_SICSTUS_H;
             let dt = *dt;
             let initialized = dt.psp_prolog_initialized.unwrap()();
@@ -88,6 +83,19 @@ impl Sicstus {
     }
 }
 
+/// Get the dispatch table of the runtime linked into the process, which is available before it is initialized.
+fn dispatch() -> *mut SICSTUS_API_STRUCT {
+    unsafe {
+        #[cfg(sicstus_version = "4.7.1")]
+        let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40701(core::ptr::null_mut());
+        #[cfg(sicstus_version = "4.8.0")]
+        let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40800(core::ptr::null_mut());
+        #[cfg(sicstus_version = "4.9.0")]
+        let sicstus: *mut SICSTUS_API_STRUCT = SP_get_dispatch_40900(core::ptr::null_mut());
+        sicstus
+    }
+}
+
 /// We use a macro to generate functions that call the dispatch table. This makes for a slightly nicer API similar to that of C.
 /// The macro makes it easier to maintain the implementations in case of changes.
 macro_rules! define_dispatch_fns {
@@ -320,6 +328,8 @@ define_dispatch_fns! {
     SP_realloc(ptr: *mut c_void, size: usize) -> *mut c_void,
     #[field_name=(pSP_register_atom)]
     SP_register_atom(atom: SP_atom) -> c_int,
+    #[field_name=(pSP_reset_term_refs)]
+    SP_reset_term_refs(term: SP_term_ref),
     #[field_name=(pSP_restore)]
     SP_restore(filenmae: *const c_char) -> c_int,
     #[field_name=(pSP_set_argv)]
@@ -454,7 +464,7 @@ macro_rules! SP_fprintf {
 macro_rules! SP_open_query {
     ($predicate:expr,$($arg:expr),*) => {
         unsafe {
-            $crate::sicstus().dt.pSP_open_query($predicate,$($arg),*)
+            $crate::sicstus().dt.pSP_open_query.expect("Crashed getting function from dipatch table")($predicate,$($arg),*)
         }
     }
 }
@@ -472,7 +482,7 @@ macro_rules! SP_open_query {
 macro_rules! SP_query {
     ($predicate:expr,$($arg:expr),*) => {
         unsafe {
-            $crate::sicstus().dt.pSP_query($predicate,$($arg),*)
+            $crate::sicstus().dt.pSP_query.expect("Crashed getting function from dipatch table")($predicate,$($arg),*)
         }
     }
 }
@@ -481,24 +491,43 @@ macro_rules! SP_query {
 macro_rules! SP_query_cut_fail {
     ($predicate:expr,$($arg:expr),*) => {
         unsafe {
-            $crate::sicstus().dt.pSP_query_cut_fail($predicate,$($arg),*)
+            $crate::sicstus().dt.pSP_query_cut_fail.expect("Crashed getting function from dipatch table")($predicate,$($arg),*)
         }
     }
 }
 
-// TODO Maybe make this work?
-/// In C This function starts the SICStus Prolog runtime. In rust it does not export properly. Do not use.
-pub fn SP_initialize(argc: c_int, argv: *mut *mut c_char, options: *const SP_options) -> c_int {
+/// Start the SICStus Prolog runtime when embedding it in a program. In C this is a macro around `sp_glue_initialize`.
+///
+/// Unlike the other functions this can be called before the runtime is initialized,
+/// since it does not use the dispatch table of the initialized runtime.
+/// `argv` must hold `argc` null terminated strings followed by a null pointer, like the `argv` of a C `main`.
+/// Returns [SP_SUCCESS] or [SP_ERROR].
+pub unsafe fn SP_initialize(
+    argc: c_int,
+    argv: *mut *mut c_char,
+    options: *const SP_options,
+) -> c_int {
+    let dt = *(*dispatch()).dispatch_API_SICSTUS_H;
+    dt.psp_glue_initialize
+        .expect("Crashed getting function from dipatch table")(
+        argc,
+        argv,
+        options,
+        core::ptr::null_mut(),
+        core::ptr::null_mut(),
+        0,
+        SP_GLUE_INITIALIZE_OPTION_RESTORE as c_int,
+    )
+}
+
+/// Whether the runtime is initialized, either by [SP_initialize] or because the code was loaded by Prolog.
+/// This can be called before the runtime is initialized.
+pub fn SP_is_initialized() -> bool {
     unsafe {
-        sp_glue_initialize(
-            argc,
-            argv,
-            options,
-            core::ptr::null_mut(),
-            core::ptr::null_mut(),
-            0,
-            SP_GLUE_INITIALIZE_OPTION_RESTORE as c_int,
-        )
+        let dt = *(*dispatch()).dispatch_API_SICSTUS_H;
+        dt.psp_prolog_initialized
+            .expect("Crashed getting function from dipatch table")()
+            != 0
     }
 }
 
@@ -1,33 +1,41 @@
-use crate::error::SicstusRsError;
+use crate::error::Error;
 use crate::sys;
 use crate::SP_atom;
 use alloc::string::String;
 use sicstus_sys::SP_term_ref;
 
-use core::cmp::Ordering;
+use core::{cmp::Ordering, ffi::CStr};
 
-use crate::TermRef;
+use crate::{convert::type_error, TermRef, TermScope};
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Atom {
-    term_ref: TermRef,
     atom_id: SP_atom,
     name: String,
 }
 
 impl Atom {
+    /// Create or look up the atom `name` and keep it alive while the [Atom] lives.
+    ///
+    /// # Panics
+    /// If `name` contains a null byte, see [Atom::try_new].
     pub fn new(name: String) -> Self {
-        let atom_id = sys::sp_atom_from_string(&name).unwrap();
-        sys::sp_register_atom(atom_id).unwrap();
-        let mut term_ref = TermRef::new();
-        term_ref.put_atom(atom_id).unwrap();
-        Atom {
-            term_ref,
-            atom_id,
-            name,
+        match Atom::try_new(&name) {
+            Ok(atom) => atom,
+            Err(e) => panic!("could not create the atom {:?}: {}", name, e),
         }
     }
 
+    /// Create or look up the atom `name`. Fails if `name` contains a null byte.
+    pub fn try_new(name: &str) -> Result<Self, Error> {
+        let atom_id = sys::sp_atom_from_string(name)?;
+        sys::sp_register_atom(atom_id)?;
+        Ok(Atom {
+            atom_id,
+            name: String::from(name),
+        })
+    }
+
     pub fn len(&self) -> usize {
         sys::sp_atom_length(self.atom_id)
     }
@@ -44,6 +52,12 @@ impl Atom {
         &self.name
     }
 
+    /// The UTF-8 text of the atom as SICStus stores it, without copying.
+    pub fn as_cstr(&self) -> &CStr {
+        // The atom is registered for as long as self lives.
+        unsafe { sys::sp_string_from_atom_cstr(self.atom_id) }
+    }
+
     pub fn as_string(&self) -> String {
         self.name.clone()
     }
@@ -52,18 +66,6 @@ impl Atom {
         self.atom_id
     }
 
-    pub fn as_term_ref(&self) -> &TermRef {
-        &self.term_ref
-    }
-
-    pub fn as_mut_term_ref(&mut self) -> &mut TermRef {
-        &mut self.term_ref
-    }
-
-    pub fn unify(&mut self, other: &TermRef) -> Result<(), SicstusRsError> {
-        self.term_ref.unify(other).map_err(|e| e.into())
-    }
-
     pub fn from_string(name: String) -> Self {
         Atom::new(name)
     }
@@ -75,6 +77,12 @@ impl Drop for Atom {
     }
 }
 
+impl Clone for Atom {
+    fn clone(&self) -> Self {
+        Atom::from(self.atom_id)
+    }
+}
+
 impl PartialEq for Atom {
     fn eq(&self, other: &Self) -> bool {
         self.cmp(other) == Ordering::Equal
@@ -89,31 +97,31 @@ impl PartialOrd for Atom {
     }
 }
 
+/// Atoms are ordered like Prolog orders them, by the character codes of their names.
 impl Ord for Atom {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.term_ref.cmp(&other.term_ref)
+        self.name.cmp(&other.name)
     }
 }
 
 impl From<SP_atom> for Atom {
     fn from(atom_id: SP_atom) -> Self {
         let name = sys::sp_string_from_atom(atom_id);
-        let mut term_ref = TermRef::new();
-        term_ref.put_atom(atom_id).unwrap();
-        Atom {
-            term_ref,
-            atom_id,
-            name,
-        }
+        sys::sp_register_atom(atom_id).unwrap();
+        Atom { atom_id, name }
     }
 }
 
+/// # Panics
+/// If the name contains a null byte, see [Atom::try_new].
 impl From<String> for Atom {
     fn from(name: String) -> Self {
         Atom::new(name)
     }
 }
 
+/// # Panics
+/// If the name contains a null byte, see [Atom::try_new].
 impl From<&str> for Atom {
     fn from(name: &str) -> Self {
         Atom::new(String::from(name))
@@ -133,15 +141,15 @@ impl From<&Atom> for SP_atom {
 }
 
 impl TryFrom<SP_term_ref> for Atom {
-    type Error = SicstusRsError;
-    fn try_from(term_ref: SP_term_ref) -> Result<Self, SicstusRsError> {
+    type Error = Error;
+    fn try_from(term_ref: SP_term_ref) -> Result<Self, Error> {
         if !sys::sp_is_atom(term_ref) {
-            return Err(SicstusRsError::TypeError(String::from(
-                "Given Term is not an atom",
-            )));
+            // The term ref was created before the scope, so it outlives it.
+            return Err(TermScope::run(|scope| {
+                type_error("atom", &unsafe { TermRef::from_raw(scope, term_ref) })
+            }));
         }
-        let atom_id =
-            crate::sys::sp_get_atom(term_ref).map_err(|e| SicstusRsError::InternalError(e))?;
+        let atom_id = crate::sys::sp_get_atom(term_ref)?;
         Ok(atom_id.into())
     }
 }
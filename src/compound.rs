@@ -0,0 +1,213 @@
+//! Building terms in Rust code.
+//!
+//! [Compound] builds a compound term argument by argument.
+//! The [term!](crate::term!) macro builds a whole term from Prolog-like syntax:
+//!
+//! ```ignore
+//! let term = Compound::new("foo")
+//!     .arg(Atom::from("bar"))
+//!     .arg(42)
+//!     .arg(list![1, "a", 2.5])
+//!     .build(scope)?;
+//! let same = term!(scope; foo(bar, 42, [1, a, 2.5]))?;
+//! let point = term!(scope; point({x}, {y}, _, [H | T], H))?;
+//! ```
+
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{
+    convert::{functor_term, ToTerm},
+    error::Error,
+    exception::atom_term,
+    TermRef, TermScope,
+};
+
+/// A builder for the compound term `name(Args...)`.
+///
+/// The arguments are converted when the term is built, so a [Compound] can itself be the argument of another one.
+/// Without arguments it builds the atom `name`.
+pub struct Compound<'a> {
+    name: String,
+    args: Vec<Box<dyn ToTerm + 'a>>,
+}
+
+impl<'a> Compound<'a> {
+    pub fn new(name: &str) -> Self {
+        Compound {
+            name: String::from(name),
+            args: Vec::new(),
+        }
+    }
+
+    /// Append an argument.
+    pub fn arg<T: ToTerm + 'a>(mut self, value: T) -> Self {
+        self.args.push(Box::new(value));
+        self
+    }
+
+    pub fn arity(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Build the term on the Prolog heap.
+    pub fn build<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.to_term(scope))
+            .collect::<Result<Vec<_>, _>>()?;
+        functor_term(scope, &self.name, &args)
+    }
+}
+
+impl ToTerm for Compound<'_> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        self.build(scope)
+    }
+}
+
+impl fmt::Debug for Compound<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Compound({}/{})", self.name, self.args.len())
+    }
+}
+
+/// The variables of one [term!](crate::term!) invocation, so the same name is the same variable.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct TermVars<'s> {
+    scope: TermScope<'s>,
+    variables: BTreeMap<&'static str, TermRef<'s>>,
+}
+
+impl<'s> TermVars<'s> {
+    pub fn new(scope: TermScope<'s>) -> Self {
+        TermVars {
+            scope,
+            variables: BTreeMap::new(),
+        }
+    }
+
+    /// The scope the terms are built in.
+    pub fn scope(&self) -> TermScope<'s> {
+        self.scope
+    }
+
+    /// An identifier is a variable if it starts with an uppercase letter or underscore, like in Prolog.
+    pub fn ident(&mut self, name: &'static str) -> Result<TermRef<'s>, Error> {
+        let scope = self.scope;
+        match name.chars().next() {
+            Some(c) if c.is_uppercase() || c == '_' => Ok(self
+                .variables
+                .entry(name)
+                .or_insert_with(|| TermRef::new_variable(scope))
+                .clone()),
+            _ => atom_term(scope, name),
+        }
+    }
+}
+
+/// Build a term from Prolog-like syntax in the given [TermScope](crate::TermScope).
+/// Evaluates to a `Result<TermRef, Error>`.
+///
+/// * Lowercase identifiers are atoms and uppercase ones are variables. `_` is a fresh variable.
+/// * `name(Args...)` is a compound term, and `"any name"(Args...)` one whose name is not an identifier.
+/// * `[A, B | Tail]` and `[]` are lists.
+/// * Number, boolean and string literals are converted with [ToTerm], so strings become atoms.
+/// * `{expr}` inserts any Rust value that implements [ToTerm].
+///
+/// Operators are not supported. Write `{(key, value)}` for the pair `Key-Value`.
+///
+/// ```ignore
+/// let name = "Ada";
+/// let term = term!(scope; person({name}, Age, [first, second | _]))?;
+/// ```
+#[macro_export]
+macro_rules! term {
+    ($scope:expr; $($t:tt)+) => {{
+        #[allow(unused_mut, unused_variables)]
+        let mut vars = $crate::compound::TermVars::new($scope);
+        #[allow(clippy::redundant_closure_call)]
+        let term = (|| -> ::core::result::Result<$crate::TermRef<'_>, $crate::Error> {
+            ::core::result::Result::Ok($crate::__term!(vars; $($t)+))
+        })();
+        term
+    }};
+}
+
+/// Build a list of Rust values that implement [ToTerm], like `vec!`.
+/// The list implements [ToTerm] itself, so it can be the argument of a [Compound].
+#[macro_export]
+macro_rules! list {
+    ($($x:expr),* $(,)?) => {
+        $crate::compound::__private::vec![
+            $($crate::compound::__private::Box::new($x) as $crate::compound::__private::Box<dyn $crate::ToTerm + '_>),*
+        ]
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{boxed::Box, vec};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __term {
+    // A Rust value.
+    ($v:ident; { $e:expr }) => {
+        $crate::ToTerm::to_term(&$e, $v.scope())?
+    };
+    ($v:ident; _) => {
+        $crate::TermRef::new_variable($v.scope())
+    };
+    ($v:ident; $lit:literal) => {
+        $crate::ToTerm::to_term(&$lit, $v.scope())?
+    };
+    ($v:ident; $name:ident ( $($args:tt)* )) => {
+        $crate::convert::functor_term($v.scope(), stringify!($name), &$crate::__term!(@args $v [] [] $($args)*))?
+    };
+    ($v:ident; $name:literal ( $($args:tt)* )) => {
+        $crate::convert::functor_term($v.scope(), $name, &$crate::__term!(@args $v [] [] $($args)*))?
+    };
+    ($v:ident; $name:ident) => {
+        $v.ident(stringify!($name))?
+    };
+    ($v:ident; [ $($items:tt)* ]) => {
+        $crate::__term!(@list $v [] [] $($items)*)
+    };
+
+    // Split arguments at commas into an array of terms.
+    (@args $v:ident [$($done:expr,)*] [$($cur:tt)+] , $($rest:tt)*) => {
+        $crate::__term!(@args $v [$($done,)* $crate::__term!($v; $($cur)+),] [] $($rest)*)
+    };
+    (@args $v:ident [$($done:expr,)*] [$($cur:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__term!(@args $v [$($done,)*] [$($cur)* $next] $($rest)*)
+    };
+    (@args $v:ident [$($done:expr,)*] [$($cur:tt)+]) => {
+        [$($done,)* $crate::__term!($v; $($cur)+)]
+    };
+    (@args $v:ident [$($done:expr,)*] []) => {
+        [$($done),*]
+    };
+
+    // Split list items at commas, up to an optional `| Tail`.
+    (@list $v:ident [$($done:expr,)*] [$($cur:tt)+] , $($rest:tt)*) => {
+        $crate::__term!(@list $v [$($done,)* $crate::__term!($v; $($cur)+),] [] $($rest)*)
+    };
+    (@list $v:ident [$($done:expr,)*] [$($cur:tt)+] | $($tail:tt)+) => {
+        $crate::PrologList::from($crate::compound::__private::vec![$($done,)* $crate::__term!($v; $($cur)+)])
+            .tail($crate::__term!($v; $($tail)+))
+            .build($v.scope())?
+    };
+    (@list $v:ident [$($done:expr,)*] [$($cur:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__term!(@list $v [$($done,)*] [$($cur)* $next] $($rest)*)
+    };
+    (@list $v:ident [$($done:expr,)*] [$($cur:tt)+]) => {
+        $crate::PrologList::from($crate::compound::__private::vec![$($done,)* $crate::__term!($v; $($cur)+)]).build($v.scope())?
+    };
+    (@list $v:ident [$($done:expr,)*] []) => {
+        $crate::PrologList::from($crate::compound::__private::vec![$($done),*]).build($v.scope())?
+    };
+}
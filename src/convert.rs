@@ -0,0 +1,660 @@
+//! Conversions between Rust values and Prolog terms.
+//!
+//! [ToTerm] and [FromTerm] are implemented for the primitive types and can be derived for structs and enums
+//! with the `derive` feature:
+//!
+//! ```ignore
+//! #[derive(ToTerm, FromTerm)]
+//! #[term(name = "pt")]
+//! struct Point {
+//!     x: i64,
+//!     y: i64,
+//!     #[term(skip)]
+//!     cached_norm: Option<f64>,
+//! }
+//! ```
+//!
+//! `Point { x: 1, y: 2, .. }` is converted to the term `pt(1, 2)`.
+//! The attributes understood by the derive macros are:
+//!
+//! * `#[term(name = "...")]` on a struct or variant sets the functor name. By default it is the snake case type name.
+//! * `#[term(arg = N)]` on a field places it at argument N, counting from 1. Other fields fill the remaining positions in order.
+//! * `#[term(codes)]` on a string field encodes it as a code list instead of an atom.
+//! * `#[term(skip)]` on a field leaves it out of the term. It is set to its [Default] when converting back.
+//!
+//! Containers are converted as follows:
+//!
+//! | Rust                     | Prolog                                  |
+//! |--------------------------|-----------------------------------------|
+//! | `Option<T>`              | `none` / `some(X)`                      |
+//! | `Vec<T>`, `[T; N]`       | list                                    |
+//! | `(A, B)`                 | pair `A-B`, `(A, B)` is read as well    |
+//! | `(A, B, C)` and longer   | `(A, B, C)`, i.e. `','(A, ','(B, C))`   |
+//! | `BTreeMap<K, V>`         | list of `Key-Value` pairs               |
+//!
+//! Terms are created in a [TermScope], and values can also be read back with `TryFrom<&TermRef>`:
+//!
+//! ```ignore
+//! TermScope::run(|scope| {
+//!     let term = u64::MAX.to_term(scope)?;
+//!     let value = u64::try_from(&term)?;
+//!     let pairs = BTreeMap::from([("a", 1), ("b", 2)]).to_term(scope)?;
+//!     Ok(())
+//! })
+//! ```
+//!
+//! Integers beyond 64 bits are bignums in Prolog. They convert from and to `i128` and `u128`,
+//! and with the `num-bigint` feature from and to `BigInt` and `BigUint` of any size.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    error::Error,
+    exception::{atom_term, compound_term},
+    sys, Atom, PrologList, Term, TermRef, TermScope,
+};
+
+/// Convert a Rust value into a new Prolog term.
+pub trait ToTerm {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error>;
+}
+
+/// Convert a Prolog term into a Rust value.
+///
+/// The lifetime is the one of the term, so that values like [TermRef] can keep parts of it.
+pub trait FromTerm<'s>: Sized {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error>;
+}
+
+/// Build the term `name(Args...)`, or the atom `name` if there are no arguments.
+pub fn functor_term<'s>(
+    scope: TermScope<'s>,
+    name: &str,
+    args: &[TermRef<'_>],
+) -> Result<TermRef<'s>, Error> {
+    if args.is_empty() {
+        atom_term(scope, name)
+    } else {
+        compound_term(scope, name, args)
+    }
+}
+
+/// The name and arity of an atom or compound term.
+pub fn functor_of(term: &TermRef) -> Result<(String, usize), Error> {
+    if !(term.is_atom() || term.is_compound()) {
+        return Err(type_error("callable", term));
+    }
+    let (name, arity) = sys::sp_get_functor(term.term_ref())?;
+    Ok((sys::sp_string_from_atom(name), arity))
+}
+
+/// Check that the term is an atom or compound term with the given name and arity.
+pub fn check_functor(term: &TermRef, name: &str, arity: usize) -> Result<(), Error> {
+    match functor_of(term) {
+        Ok((found, found_arity)) if found == name && found_arity == arity => Ok(()),
+        _ => Err(type_error(&format!("{}/{}", name, arity), term)),
+    }
+}
+
+/// Build a proper list from its elements.
+pub fn list_term<'s>(scope: TermScope<'s>, items: Vec<TermRef<'s>>) -> Result<TermRef<'s>, Error> {
+    PrologList::from(items).build(scope)
+}
+
+/// Encode text as a list of character codes.
+pub fn codes_term<'s>(scope: TermScope<'s>, text: &str) -> Result<TermRef<'s>, Error> {
+    list_term(
+        scope,
+        text.chars()
+            .map(|c| TermRef::new_integer(scope, c as i64))
+            .collect(),
+    )
+}
+
+/// Decode text from an atom or a list of character codes.
+/// `[]` is the empty code list, so it decodes to the empty string rather than to the atom name.
+pub fn text_from_term(term: &TermRef) -> Result<String, Error> {
+    if is_nil(term) {
+        return Ok(String::new());
+    }
+    if term.is_atom() {
+        return Ok(functor_of(term)?.0);
+    }
+    let mut text = String::new();
+    for code in list_items(term, "text")? {
+        let c = u32::try_from(i64::from_term(&code)?)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| type_error("character_code", &code))?;
+        text.push(c);
+    }
+    Ok(text)
+}
+
+pub(crate) fn is_nil(term: &TermRef) -> bool {
+    term.is_atom() && functor_of(term).is_ok_and(|(name, _)| name == "[]")
+}
+
+/// The elements of a proper list.
+fn list_items<'s>(term: &TermRef<'s>, expected: &str) -> Result<Vec<TermRef<'s>>, Error> {
+    term.list_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|_| type_error(expected, term))
+}
+
+/// The error for a term that does not have the expected type.
+/// The culprit is copied, or left as a variable if that fails.
+pub fn type_error(expected: &str, culprit: &TermRef) -> Error {
+    Error::TypeError {
+        expected: expected.to_string(),
+        culprit: culprit.to_term().unwrap_or(Term::Var(0)),
+    }
+}
+
+impl ToTerm for TermRef<'_> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let mut term = TermRef::new(scope);
+        term.put_term(self)?;
+        Ok(term)
+    }
+}
+
+impl<'s> FromTerm<'s> for TermRef<'s> {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        Ok(term.clone())
+    }
+}
+
+impl ToTerm for Atom {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let mut term = TermRef::new(scope);
+        term.put_atom(self.atom_id())?;
+        Ok(term)
+    }
+}
+
+impl<'s> FromTerm<'s> for Atom {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if !term.is_atom() {
+            return Err(type_error("atom", term));
+        }
+        term.get_atom()
+    }
+}
+
+impl ToTerm for i64 {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        Ok(TermRef::new_integer(scope, *self))
+    }
+}
+
+impl<'s> FromTerm<'s> for i64 {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if !term.is_integer() {
+            return Err(type_error("integer", term));
+        }
+        // Fails for bignums.
+        term.get_integer().map_err(|_| type_error("i64", term))
+    }
+}
+
+/// Integers that do not fit into an i64 are bignums in Prolog.
+impl ToTerm for i128 {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        match i64::try_from(*self) {
+            Ok(integer) => integer.to_term(scope),
+            Err(_) => bigint_bytes_term(scope, &self.to_le_bytes()),
+        }
+    }
+}
+
+impl<'s> FromTerm<'s> for i128 {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if !term.is_integer() {
+            return Err(type_error("integer", term));
+        }
+        if let Ok(integer) = term.get_integer() {
+            return Ok(integer.into());
+        }
+        let bytes = term.get_integer_bytes()?;
+        let bytes = fit_integer_bytes(&bytes, true).ok_or_else(|| type_error("i128", term))?;
+        Ok(i128::from_le_bytes(bytes))
+    }
+}
+
+impl ToTerm for u128 {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        match i64::try_from(*self) {
+            Ok(integer) => integer.to_term(scope),
+            Err(_) => {
+                // A zero byte on top keeps the two's complement non-negative.
+                let mut bytes = Vec::from(self.to_le_bytes());
+                bytes.push(0);
+                bigint_bytes_term(scope, &bytes)
+            }
+        }
+    }
+}
+
+impl<'s> FromTerm<'s> for u128 {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if !term.is_integer() {
+            return Err(type_error("integer", term));
+        }
+        if let Ok(integer) = term.get_integer() {
+            return u128::try_from(integer).map_err(|_| type_error("u128", term));
+        }
+        let bytes = term.get_integer_bytes()?;
+        let bytes = fit_integer_bytes(&bytes, false).ok_or_else(|| type_error("u128", term))?;
+        Ok(u128::from_le_bytes(bytes))
+    }
+}
+
+/// An integer term from its two's complement bytes, least significant byte first.
+fn bigint_bytes_term<'s>(scope: TermScope<'s>, bytes: &[u8]) -> Result<TermRef<'s>, Error> {
+    let mut term = TermRef::new(scope);
+    term.put_integer_bytes(bytes, false)?;
+    Ok(term)
+}
+
+/// Sign extend or truncate two's complement bytes to `N` bytes.
+/// Returns None if the value does not fit, or if it is negative and `signed` is false.
+fn fit_integer_bytes<const N: usize>(bytes: &[u8], signed: bool) -> Option<[u8; N]> {
+    let negative = bytes.last().is_some_and(|byte| byte & 0x80 != 0);
+    if negative && !signed {
+        return None;
+    }
+    let fill = if negative { 0xff } else { 0 };
+    let mut fitted = [fill; N];
+    let n = bytes.len().min(N);
+    fitted[..n].copy_from_slice(&bytes[..n]);
+    let extends = bytes[n..].iter().all(|byte| *byte == fill);
+    // For signed values the top bit of what is left must still carry the sign.
+    let keeps_sign = !signed || (fitted[N - 1] & 0x80 != 0) == negative;
+    (extends && keeps_sign).then_some(fitted)
+}
+
+macro_rules! impl_integer_conversion {
+    ($($t:ty),*) => {
+        $(
+            impl ToTerm for $t {
+                fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+                    i128::from(*self).to_term(scope)
+                }
+            }
+
+            impl<'s> FromTerm<'s> for $t {
+                fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+                    <$t>::try_from(i128::from_term(term)?)
+                        .map_err(|_| type_error(stringify!($t), term))
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_conversion!(i8, i16, i32, u8, u16, u32, u64);
+
+impl ToTerm for isize {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        (*self as i64).to_term(scope)
+    }
+}
+
+impl<'s> FromTerm<'s> for isize {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        isize::try_from(i64::from_term(term)?).map_err(|_| type_error("isize", term))
+    }
+}
+
+impl ToTerm for usize {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        (*self as u64).to_term(scope)
+    }
+}
+
+impl<'s> FromTerm<'s> for usize {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        usize::try_from(u64::from_term(term)?).map_err(|_| type_error("usize", term))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl ToTerm for num_bigint::BigInt {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        bigint_bytes_term(scope, &self.to_signed_bytes_le())
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'s> FromTerm<'s> for num_bigint::BigInt {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if !term.is_integer() {
+            return Err(type_error("integer", term));
+        }
+        Ok(num_bigint::BigInt::from_signed_bytes_le(
+            &term.get_integer_bytes()?,
+        ))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl ToTerm for num_bigint::BigUint {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        num_bigint::BigInt::from(self.clone()).to_term(scope)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'s> FromTerm<'s> for num_bigint::BigUint {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        num_bigint::BigInt::from_term(term)?
+            .to_biguint()
+            .ok_or_else(|| type_error("not_less_than_zero", term))
+    }
+}
+
+impl ToTerm for f64 {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        Ok(TermRef::new_float(scope, *self))
+    }
+}
+
+/// Integers are accepted as well, since Prolog code often writes `1` where `1.0` is meant.
+impl<'s> FromTerm<'s> for f64 {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if term.is_float() {
+            term.get_float()
+        } else if term.is_integer() {
+            Ok(term.get_integer()? as f64)
+        } else {
+            Err(type_error("number", term))
+        }
+    }
+}
+
+impl ToTerm for f32 {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        (*self as f64).to_term(scope)
+    }
+}
+
+impl<'s> FromTerm<'s> for f32 {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        Ok(f64::from_term(term)? as f32)
+    }
+}
+
+impl ToTerm for bool {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        atom_term(scope, if *self { "true" } else { "false" })
+    }
+}
+
+impl<'s> FromTerm<'s> for bool {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        match functor_of(term) {
+            Ok((name, 0)) if name == "true" => Ok(true),
+            Ok((name, 0)) if name == "false" => Ok(false),
+            _ => Err(type_error("boolean", term)),
+        }
+    }
+}
+
+impl ToTerm for str {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        atom_term(scope, self)
+    }
+}
+
+impl ToTerm for String {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        atom_term(scope, self)
+    }
+}
+
+impl<'s> FromTerm<'s> for String {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        text_from_term(term)
+    }
+}
+
+impl ToTerm for char {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let mut buf = [0; 4];
+        atom_term(scope, self.encode_utf8(&mut buf))
+    }
+}
+
+/// A character is either a single character atom or a character code.
+impl<'s> FromTerm<'s> for char {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        if term.is_integer() {
+            return u32::try_from(term.get_integer()?)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| type_error("character", term));
+        }
+        let text = text_from_term(term)?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(type_error("character", term)),
+        }
+    }
+}
+
+impl<T: ToTerm + ?Sized> ToTerm for &T {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        (**self).to_term(scope)
+    }
+}
+
+impl<T: ToTerm + ?Sized> ToTerm for Box<T> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        (**self).to_term(scope)
+    }
+}
+
+impl<T: ToTerm> ToTerm for Option<T> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        match self {
+            Some(value) => compound_term(scope, "some", &[value.to_term(scope)?]),
+            None => atom_term(scope, "none"),
+        }
+    }
+}
+
+impl<'s, T: FromTerm<'s>> FromTerm<'s> for Option<T> {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        match functor_of(term) {
+            Ok((name, 0)) if name == "none" => Ok(None),
+            Ok((name, 1)) if name == "some" => Ok(Some(T::from_term(&term.get_arg(1)?)?)),
+            _ => Err(type_error("option", term)),
+        }
+    }
+}
+
+impl<T: ToTerm> ToTerm for [T] {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let items = self
+            .iter()
+            .map(|item| item.to_term(scope))
+            .collect::<Result<Vec<_>, _>>()?;
+        list_term(scope, items)
+    }
+}
+
+impl<T: ToTerm> ToTerm for Vec<T> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        self.as_slice().to_term(scope)
+    }
+}
+
+impl<'s, T: FromTerm<'s>> FromTerm<'s> for Vec<T> {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        list_items(term, "list")?.iter().map(T::from_term).collect()
+    }
+}
+
+impl<T: ToTerm, const N: usize> ToTerm for [T; N] {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        self.as_slice().to_term(scope)
+    }
+}
+
+impl<'s, T: FromTerm<'s>, const N: usize> FromTerm<'s> for [T; N] {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        Vec::<T>::from_term(term)?
+            .try_into()
+            .map_err(|_| type_error(&format!("list_of_length_{}", N), term))
+    }
+}
+
+impl<A: ToTerm, B: ToTerm> ToTerm for (A, B) {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        compound_term(
+            scope,
+            "-",
+            &[self.0.to_term(scope)?, self.1.to_term(scope)?],
+        )
+    }
+}
+
+impl<'s, A: FromTerm<'s>, B: FromTerm<'s>> FromTerm<'s> for (A, B) {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        match functor_of(term) {
+            Ok((name, 2)) if name == "-" || name == "," => Ok((
+                A::from_term(&term.get_arg(1)?)?,
+                B::from_term(&term.get_arg(2)?)?,
+            )),
+            _ => Err(type_error("pair", term)),
+        }
+    }
+}
+
+/// Tuples with more than two elements are right nested `','/2` terms.
+macro_rules! impl_tuple_conversion {
+    ($($t:ident),*; $last:ident) => {
+        impl<$($t: ToTerm,)* $last: ToTerm> ToTerm for ($($t,)* $last) {
+            #[allow(non_snake_case)]
+            fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+                let ($($t,)* $last) = self;
+                let mut term = $last.to_term(scope)?;
+                for item in [$($t.to_term(scope)?),*].into_iter().rev() {
+                    term = compound_term(scope, ",", &[item, term])?;
+                }
+                Ok(term)
+            }
+        }
+
+        impl<'s, $($t: FromTerm<'s>,)* $last: FromTerm<'s>> FromTerm<'s> for ($($t,)* $last) {
+            #[allow(non_snake_case)]
+            fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+                let mut rest = term.clone();
+                $(
+                    check_functor(&rest, ",", 2).map_err(|_| type_error("tuple", term))?;
+                    let $t = $t::from_term(&rest.get_arg(1)?)?;
+                    rest = rest.get_arg(2)?;
+                )*
+                Ok(($($t,)* $last::from_term(&rest)?))
+            }
+        }
+    };
+}
+
+impl_tuple_conversion!(A, B; C);
+impl_tuple_conversion!(A, B, C; D);
+impl_tuple_conversion!(A, B, C, D; E);
+impl_tuple_conversion!(A, B, C, D, E; F);
+
+impl<K: ToTerm, V: ToTerm> ToTerm for BTreeMap<K, V> {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let pairs = self
+            .iter()
+            .map(|pair| pair.to_term(scope))
+            .collect::<Result<Vec<_>, _>>()?;
+        list_term(scope, pairs)
+    }
+}
+
+/// Later pairs replace earlier ones with the same key.
+impl<'s, K: FromTerm<'s> + Ord, V: FromTerm<'s>> FromTerm<'s> for BTreeMap<K, V> {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        list_items(term, "list")?
+            .iter()
+            .map(<(K, V)>::from_term)
+            .collect()
+    }
+}
+
+/// Conversions out of a [TermRef], returning a typed error if the term does not fit.
+macro_rules! impl_try_from_term_ref {
+    ($($t:ty),*) => {
+        $(
+            impl<'s> TryFrom<&TermRef<'s>> for $t {
+                type Error = Error;
+                fn try_from(term: &TermRef<'s>) -> Result<Self, Error> {
+                    <$t>::from_term(term)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_term_ref!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char, String
+);
+
+/// Implement `TryFrom<&TermRef>` for a generic container via [FromTerm].
+macro_rules! impl_try_from_container {
+    ($(<$($g:ident $(: $bound:ident)?),* $(; const $n:ident)?> $t:ty),* $(,)?) => {
+        $(
+            impl<'s, $($g: FromTerm<'s> $(+ $bound)?),* $(, const $n: usize)?> TryFrom<&TermRef<'s>> for $t {
+                type Error = Error;
+                fn try_from(term: &TermRef<'s>) -> Result<Self, Error> {
+                    <$t>::from_term(term)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_container!(
+    <T> Option<T>,
+    <T> Vec<T>,
+    <T; const N> [T; N],
+    <A, B> (A, B),
+    <A, B, C> (A, B, C),
+    <A, B, C, D> (A, B, C, D),
+    <A, B, C, D, E> (A, B, C, D, E),
+    <A, B, C, D, E, F> (A, B, C, D, E, F),
+    <K: Ord, V> BTreeMap<K, V>,
+);
+
+#[cfg(test)]
+#[test]
+fn test_fit_integer_bytes() {
+    assert_eq!(fit_integer_bytes::<2>(&[0xff], true), Some([0xff, 0xff]));
+    assert_eq!(
+        fit_integer_bytes::<2>(&[0x80, 0x00], true),
+        Some([0x80, 0x00])
+    );
+    assert_eq!(fit_integer_bytes::<2>(&[0x00, 0x80, 0x00], true), None);
+    assert_eq!(
+        fit_integer_bytes::<2>(&[0x00, 0x80, 0x00], false),
+        Some([0x00, 0x80])
+    );
+    assert_eq!(
+        fit_integer_bytes::<2>(&[0x00, 0x80, 0xff], true),
+        Some([0x00, 0x80])
+    );
+    assert_eq!(fit_integer_bytes::<2>(&[0xff], false), None);
+    assert_eq!(fit_integer_bytes::<2>(&[1, 2, 3], false), None);
+}
@@ -0,0 +1,500 @@
+//! Deserialize Rust values from Prolog terms with serde.
+//!
+//! This is the inverse of the mapping described in [ser](crate::ser).
+//! Strings are accepted both as atoms and as code lists, independent of the [StringStyle](crate::StringStyle)
+//! that was used to serialize them.
+
+use alloc::{format, string::String};
+use serde::de::{
+    self, value::StrDeserializer, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+
+use crate::{
+    convert::{text_from_term, type_error},
+    error::Error,
+    sys,
+    util::snake_case,
+    FromTerm, TermRef,
+};
+
+/// Deserialize a value from a Prolog term.
+pub fn from_term<T: DeserializeOwned>(term: &TermRef) -> Result<T, Error> {
+    T::deserialize(TermDeserializer::new(term.clone()))
+}
+
+/// A serde [Deserializer](de::Deserializer) reading from a [TermRef].
+pub struct TermDeserializer<'s> {
+    term: TermRef<'s>,
+}
+
+impl<'s> TermDeserializer<'s> {
+    pub fn new(term: TermRef<'s>) -> Self {
+        TermDeserializer { term }
+    }
+
+    fn functor(&self) -> Option<(String, usize)> {
+        let (name, arity) = sys::sp_get_functor(self.term.term_ref()).ok()?;
+        Some((sys::sp_string_from_atom(name), arity))
+    }
+
+    fn is_nil(&self) -> bool {
+        self.term.is_atom() && self.functor().is_some_and(|(name, _)| name == "[]")
+    }
+
+    fn atom(&self) -> Option<String> {
+        if self.term.is_atom() {
+            self.functor().map(|(name, _)| name)
+        } else {
+            None
+        }
+    }
+
+    fn integer(&self) -> Result<i64, Error> {
+        if !self.term.is_integer() {
+            return Err(self.type_error("integer"));
+        }
+        self.term.get_integer()
+    }
+
+    /// An integer of type `T`, checked like [FromTerm] does, so bignums are read as well.
+    fn integer_of<T: FromTerm<'s>>(&self) -> Result<T, Error> {
+        if !self.term.is_integer() {
+            return Err(self.type_error("integer"));
+        }
+        T::from_term(&self.term)
+    }
+
+    fn text(&self) -> Result<String, Error> {
+        text_from_term(&self.term)
+    }
+
+    /// Check that the term is `name(Args...)` with the expected arity and return an accessor for the arguments.
+    fn compound(self, name: &str, arity: usize) -> Result<ArgsAccess<'s>, Error> {
+        let expected = snake_case(name);
+        match self.functor() {
+            Some((found, found_arity)) if found == expected && found_arity == arity => {
+                Ok(ArgsAccess::new(self.term, arity))
+            }
+            _ => Err(self.type_error(&format!("{}/{}", expected, arity))),
+        }
+    }
+
+    fn type_error(&self, expected: &str) -> Error {
+        type_error(expected, &self.term)
+    }
+}
+
+impl<'de, 's> de::Deserializer<'de> for TermDeserializer<'s> {
+    type Error = Error;
+
+    /// Deserialize based on the type of the term.
+    /// Compound terms other than lists can not be deserialized without knowing the expected type.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.term.is_integer() {
+            // Bignums beyond 64 bits are visited as i128 or u128 if they fit.
+            match i64::from_term(&self.term) {
+                Ok(v) => visitor.visit_i64(v),
+                Err(_) => match i128::from_term(&self.term) {
+                    Ok(v) => visitor.visit_i128(v),
+                    Err(_) => visitor.visit_u128(self.integer_of()?),
+                },
+            }
+        } else if self.term.is_float() {
+            visitor.visit_f64(self.term.get_float()?)
+        } else if self.is_nil() {
+            visitor.visit_seq(ListAccess::new(self.term))
+        } else if let Some(name) = self.atom() {
+            visitor.visit_string(name)
+        } else if self.term.get_list().is_some() {
+            visitor.visit_seq(ListAccess::new(self.term))
+        } else {
+            Err(self.type_error("atomic or list"))
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.atom().as_deref() {
+            Some("true") => visitor.visit_bool(true),
+            Some("false") => visitor.visit_bool(false),
+            _ => Err(self.type_error("boolean")),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(self.integer_of()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.integer_of()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.integer_of()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.integer_of()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i128(self.integer_of()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.integer_of()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.integer_of()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.integer_of()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.integer_of()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(self.integer_of()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    /// Integers are accepted as well, since Prolog code often writes `1` where `1.0` is meant.
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.term.is_float() {
+            visitor.visit_f64(self.term.get_float()?)
+        } else if self.term.is_integer() {
+            visitor.visit_f64(self.term.get_integer()? as f64)
+        } else {
+            Err(self.type_error("number"))
+        }
+    }
+
+    /// A character is either a single character atom or a character code.
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.term.is_integer() {
+            let code = self.integer()?;
+            let c = u32::try_from(code).ok().and_then(char::from_u32);
+            return match c {
+                Some(c) => visitor.visit_char(c),
+                None => Err(self.type_error("character")),
+            };
+        }
+        let text = self.text()?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(self.type_error("character")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.text()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut bytes = alloc::vec::Vec::new();
+        let mut rest = self.term.clone();
+        while let Some((head, tail)) = rest.get_list() {
+            let byte = TermDeserializer::new(head).integer()?;
+            bytes.push(u8::try_from(byte).map_err(|_| self.type_error("byte list"))?);
+            rest = tail;
+        }
+        if !TermDeserializer::new(rest).is_nil() {
+            return Err(self.type_error("byte list"));
+        }
+        visitor.visit_byte_buf(bytes)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.atom().as_deref() == Some("none") {
+            return visitor.visit_none();
+        }
+        let mut args = self.compound("some", 1)?;
+        visitor.visit_some(TermDeserializer::new(args.next_arg()?))
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_nil() {
+            visitor.visit_unit()
+        } else {
+            Err(self.type_error("[]"))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if self.atom() == Some(snake_case(name)) {
+            visitor.visit_unit()
+        } else {
+            Err(self.type_error(&snake_case(name)))
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let mut args = self.compound(name, 1)?;
+        visitor.visit_newtype_struct(TermDeserializer::new(args.next_arg()?))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_nil() || self.term.get_list().is_some() {
+            visitor.visit_seq(ListAccess::new(self.term))
+        } else {
+            Err(self.type_error("list"))
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(self.compound(name, len)?)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_nil() || self.term.get_list().is_some() {
+            visitor.visit_map(PairsAccess {
+                list: ListAccess::new(self.term),
+                value: None,
+            })
+        } else {
+            Err(self.type_error("list of pairs"))
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(self.compound(name, fields.len())?)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let (name, arity) = self
+            .functor()
+            .ok_or_else(|| self.type_error("atom or compound"))?;
+        let variant = variants
+            .iter()
+            .find(|variant| snake_case(variant) == name)
+            .ok_or_else(|| Error::Conversion(format!("unknown variant {}/{}", name, arity)))?;
+        visitor.visit_enum(EnumAccess {
+            variant,
+            args: ArgsAccess::new(self.term, arity),
+        })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Accesses the elements of a Prolog list.
+struct ListAccess<'s> {
+    rest: TermRef<'s>,
+}
+
+impl<'s> ListAccess<'s> {
+    fn new(list: TermRef<'s>) -> Self {
+        ListAccess { rest: list }
+    }
+
+    fn next_element(&mut self) -> Result<Option<TermRef<'s>>, Error> {
+        match self.rest.get_list() {
+            Some((head, tail)) => {
+                self.rest = tail;
+                Ok(Some(head))
+            }
+            None if TermDeserializer::new(self.rest.clone()).is_nil() => Ok(None),
+            None => Err(type_error("list", &self.rest)),
+        }
+    }
+}
+
+impl<'de, 's> SeqAccess<'de> for ListAccess<'s> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.next_element()? {
+            Some(element) => seed.deserialize(TermDeserializer::new(element)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Accesses a list of `Key-Value` pairs as a map.
+struct PairsAccess<'s> {
+    list: ListAccess<'s>,
+    value: Option<TermRef<'s>>,
+}
+
+impl<'de, 's> MapAccess<'de> for PairsAccess<'s> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let pair = match self.list.next_element()? {
+            Some(pair) => pair,
+            None => return Ok(None),
+        };
+        let mut args = TermDeserializer::new(pair).compound("-", 2)?;
+        let key = args.next_arg()?;
+        self.value = Some(args.next_arg()?);
+        seed.deserialize(TermDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::Conversion(String::from("map value requested before its key")))?;
+        seed.deserialize(TermDeserializer::new(value))
+    }
+}
+
+/// Accesses the arguments of a compound term in order.
+struct ArgsAccess<'s> {
+    term: TermRef<'s>,
+    arity: usize,
+    next: usize,
+}
+
+impl<'s> ArgsAccess<'s> {
+    fn new(term: TermRef<'s>, arity: usize) -> Self {
+        ArgsAccess {
+            term,
+            arity,
+            next: 1,
+        }
+    }
+
+    /// The next argument, which compound or expect_arity checked to exist.
+    fn next_arg(&mut self) -> Result<TermRef<'s>, Error> {
+        let arg = self.term.get_arg(self.next)?;
+        self.next += 1;
+        Ok(arg)
+    }
+
+    fn expect_arity(&self, arity: usize) -> Result<(), Error> {
+        if self.arity == arity {
+            Ok(())
+        } else {
+            Err(Error::ArityMismatch {
+                expected: arity,
+                found: self.arity,
+            })
+        }
+    }
+}
+
+impl<'de, 's> SeqAccess<'de> for ArgsAccess<'s> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.next > self.arity {
+            return Ok(None);
+        }
+        let arg = self.next_arg()?;
+        seed.deserialize(TermDeserializer::new(arg)).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.arity + 1 - self.next)
+    }
+}
+
+/// Accesses the variant of an enum, which is the functor of the term.
+struct EnumAccess<'s> {
+    variant: &'static str,
+    args: ArgsAccess<'s>,
+}
+
+impl<'de, 's> de::EnumAccess<'de> for EnumAccess<'s> {
+    type Error = Error;
+    type Variant = ArgsAccess<'s>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, ArgsAccess<'s>), Error> {
+        let variant: StrDeserializer<Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+        Ok((variant, self.args))
+    }
+}
+
+impl<'de, 's> de::VariantAccess<'de> for ArgsAccess<'s> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        self.expect_arity(0)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(mut self, seed: T) -> Result<T::Value, Error> {
+        self.expect_arity(1)?;
+        seed.deserialize(TermDeserializer::new(self.next_arg()?))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.expect_arity(len)?;
+        visitor.visit_seq(self)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.expect_arity(fields.len())?;
+        visitor.visit_seq(self)
+    }
+}
@@ -1,54 +1,370 @@
-use alloc::{format, string::String};
+use alloc::{
+    ffi::NulError,
+    string::{String, ToString},
+};
+use core::{ffi::c_int, fmt};
 
 use crate::{
-    sys::{sp_raise_exception, PrologError},
-    TermRef,
+    exception::{atom_term, compound_term, PrologException},
+    sys::{sp_fail, sp_raise_exception},
+    IsoError, Term, TermRef, TermScope,
 };
 
+/// A SICStus API function that did not succeed.
 #[derive(Debug)]
-pub enum SicstusRsError {
-    /// An error that occurred in the SICStus Prolog C API.
-    InternalError(PrologError),
-    InitializationError(String),
-    AtomNotFound(PrologError),
-    InvalidName(String),
-    TypeError(String)
+pub struct ApiError {
+    /// The name of the C function, e.g. `SP_get_integer`.
+    pub function: &'static str,
+    /// The value the function returned.
+    pub code: c_int,
+    pub message: String,
 }
 
-pub fn throw_exception(message: String) {
-    let mut exception_term = TermRef::new();
-    exception_term.put_string(message.as_str()).unwrap();
-    sp_raise_exception(exception_term.term_ref());
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} returned {}: {}",
+            self.function, self.code, self.message
+        )
+    }
 }
 
-/// A Function that takes a PrologError and propagates it to Prolog without panicking.
-pub fn handle_prolog_error(error: PrologError) {
-    throw_exception(format!("Prolog error: {:?}", error));
+impl core::error::Error for ApiError {}
+
+/// The error type of this crate.
+#[derive(Debug)]
+pub enum Error {
+    /// A call to the SICStus C API failed.
+    Api(ApiError),
+    /// A call to the SICStus C API raised a Prolog exception.
+    Exception {
+        function: &'static str,
+        exception: PrologException,
+    },
+    /// A query has no further solutions.
+    NoMoreSolutions,
+    /// Two terms could not be unified.
+    UnificationFailed,
+    /// No predicate with the given indicator, e.g. `user:foo/2`, is defined.
+    PredicateNotFound(String),
+    /// A string could not be parsed as a predicate indicator.
+    MalformedIndicator(String),
+    /// A predicate was called with the wrong number of arguments.
+    ArityMismatch { expected: usize, found: usize },
+    /// An argument index outside `1..=arity` of a compound term.
+    ArgumentOutOfRange { index: usize, arity: usize },
+    /// The query was already closed or cut.
+    QueryClosed,
+    /// A more recently opened query is still open.
+    QueryNotInnermost,
+    /// A term did not have the expected type, e.g. `integer` or `atom`.
+    TypeError { expected: String, culprit: Term },
+    /// A string is not a valid name for an atom or functor.
+    InvalidName(String),
+    /// A term was not sufficiently instantiated, e.g. a list with an unbound tail.
+    InstantiationError,
+    /// Prolog text could not be read. Line and column start at 1.
+    Syntax {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// Text passed to SICStus contained a null byte.
+    InteriorNul(NulError),
+    /// A value could not be converted to or from a Prolog term.
+    Conversion(String),
+    /// The SICStus runtime could not be initialized.
+    Initialization(ApiError),
+    /// The SICStus runtime is already running, e.g. because Prolog loaded this code as a foreign resource.
+    AlreadyInitialized,
+    /// Prolog code could not be loaded. The exception is the one raised while loading, if any.
+    Load {
+        file: String,
+        exception: Option<PrologException>,
+    },
 }
 
-/// A Function that takes a SicstusRsError and propagates it to Prolog without panicking.
-pub fn handle_sicstus_rs_error(error: SicstusRsError) {
-    match error {
-        SicstusRsError::InternalError(e) => {
-            handle_prolog_error(e);
-        }
-        SicstusRsError::InitializationError(e) => {
-            throw_exception(format!("Initialization error: {:?}", e));
-        }
-        SicstusRsError::AtomNotFound(e) => {
-            handle_prolog_error(e);
+impl Error {
+    pub(crate) fn api(function: &'static str, code: c_int, message: String) -> Self {
+        Error::Api(ApiError {
+            function,
+            code,
+            message,
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api(e) => e.fmt(f),
+            Error::Exception {
+                function,
+                exception,
+            } => write!(f, "{} raised an exception: {}", function, exception),
+            Error::NoMoreSolutions => f.write_str("no more solutions"),
+            Error::UnificationFailed => f.write_str("unification failed"),
+            Error::PredicateNotFound(indicator) => {
+                write!(f, "predicate {} not found", indicator)
+            }
+            Error::MalformedIndicator(indicator) => {
+                write!(f, "malformed predicate indicator {:?}", indicator)
+            }
+            Error::ArityMismatch { expected, found } => {
+                write!(f, "expected {} arguments but got {}", expected, found)
+            }
+            Error::ArgumentOutOfRange { index, arity } => write!(
+                f,
+                "argument {} is out of range for a term with {} arguments",
+                index, arity
+            ),
+            Error::QueryClosed => f.write_str("the query is closed"),
+            Error::QueryNotInnermost => f.write_str("the query is not the innermost open query"),
+            Error::TypeError { expected, .. } => write!(f, "type error: expected {}", expected),
+            Error::InvalidName(name) => write!(f, "invalid name {:?}", name),
+            Error::InstantiationError => f.write_str("instantiation error"),
+            Error::Syntax {
+                message,
+                line,
+                column,
+            } => write!(f, "syntax error at {}:{}: {}", line, column, message),
+            Error::InteriorNul(e) => write!(f, "text contains a null byte: {}", e),
+            Error::Conversion(message) => f.write_str(message),
+            Error::Initialization(e) => write!(f, "could not initialize SICStus: {}", e),
+            Error::AlreadyInitialized => f.write_str("the SICStus runtime is already running"),
+            Error::Load {
+                file,
+                exception: Some(exception),
+            } => write!(f, "could not load {}: {}", file, exception),
+            Error::Load { file, .. } => write!(f, "could not load {}", file),
         }
-        SicstusRsError::TypeError(e) => {
-            throw_exception(format!("Type error: {:?}", e));
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Api(e) | Error::Initialization(e) => Some(e),
+            Error::Exception { exception, .. }
+            | Error::Load {
+                exception: Some(exception),
+                ..
+            } => Some(exception),
+            Error::InteriorNul(e) => Some(e),
+            _ => None,
         }
-        SicstusRsError::InvalidName(e) => {
-            throw_exception(format!("Invalid name error: {:?}", e));
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(error: NulError) -> Self {
+        Error::InteriorNul(error)
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(error: ApiError) -> Self {
+        Error::Api(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Conversion(message.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Conversion(message.to_string())
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Raise `message` as a `system_error` exception, see [ErrorTerm::system_error].
+pub fn throw_exception(message: String) {
+    ErrorTerm::system_error().message(&message).raise();
+}
+
+/// A Function that takes an Error and propagates it to Prolog without panicking.
+/// Exceptions raised by Prolog are passed on as they are,
+/// everything else is raised as the matching ISO error term, see [ErrorTerm].
+pub fn handle_error(error: Error) {
+    match error {
+        Error::Exception { exception, .. }
+        | Error::Load {
+            exception: Some(exception),
+            ..
+        } => TermScope::run(|scope| match exception.term().to_term_ref(scope) {
+            Ok(term) => sp_raise_exception(term.term_ref()),
+            Err(_) => ErrorTerm::from(IsoError::SystemError).raise(),
+        }),
+        error => ErrorTerm::from(error).raise(),
+    }
+}
+
+/// Builder for ISO error terms of the form `error(Formal, context(Name/Arity, Message))`.
+///
+/// Raising these from foreign predicates lets Prolog code catch them like the errors of built-in predicates,
+/// e.g. with `catch(Goal, error(type_error(_, _), _), Handler)`.
+/// The predicate and the message are left unbound unless they are set.
+///
+/// ```ignore
+/// ErrorTerm::type_error("integer", culprit)
+///     .predicate("my_pred", 2)
+///     .message("the first argument must be an integer")
+///     .raise();
+/// ```
+#[derive(Debug)]
+pub struct ErrorTerm {
+    error: IsoError,
+    predicate: Option<(String, usize)>,
+    message: Option<String>,
+}
+
+impl ErrorTerm {
+    pub fn new(error: IsoError) -> Self {
+        ErrorTerm {
+            error,
+            predicate: None,
+            message: None,
         }
-    };
+    }
+
+    pub fn instantiation_error() -> Self {
+        ErrorTerm::new(IsoError::InstantiationError)
+    }
+
+    pub fn type_error(expected: &str, culprit: Term) -> Self {
+        ErrorTerm::new(IsoError::TypeError {
+            expected: expected.to_string(),
+            culprit,
+        })
+    }
+
+    pub fn domain_error(domain: &str, culprit: Term) -> Self {
+        ErrorTerm::new(IsoError::DomainError {
+            domain: domain.to_string(),
+            culprit,
+        })
+    }
+
+    pub fn existence_error(object_type: &str, culprit: Term) -> Self {
+        ErrorTerm::new(IsoError::ExistenceError {
+            object_type: object_type.to_string(),
+            culprit,
+        })
+    }
+
+    pub fn system_error() -> Self {
+        ErrorTerm::new(IsoError::SystemError)
+    }
+
+    /// Set the predicate that raised the error.
+    pub fn predicate(mut self, name: &str, arity: usize) -> Self {
+        self.predicate = Some((name.to_string(), arity));
+        self
+    }
+
+    /// Set a human readable message describing the error.
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+
+    pub fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>> {
+        let predicate = match &self.predicate {
+            Some((name, arity)) => compound_term(
+                scope,
+                "/",
+                &[
+                    atom_term(scope, name)?,
+                    TermRef::new_integer(scope, *arity as i64),
+                ],
+            )?,
+            None => TermRef::new_variable(scope),
+        };
+        let message = match &self.message {
+            Some(message) => atom_term(scope, message)?,
+            None => TermRef::new_variable(scope),
+        };
+        let context = compound_term(scope, "context", &[predicate, message])?;
+        compound_term(scope, "error", &[self.error.to_term(scope)?, context])
+    }
+
+    /// Raise the error term as an exception. It will be detected when the program returns to Prolog.
+    pub fn raise(self) {
+        // Building the term only fails if SICStus runs out of memory. Then try a plain system error,
+        // and as a last resort let the call fail.
+        TermScope::run(|scope| {
+            match self
+                .to_term(scope)
+                .or_else(|_| ErrorTerm::system_error().to_term(scope))
+            {
+                Ok(term) => sp_raise_exception(term.term_ref()),
+                Err(_) => sp_fail(),
+            }
+        })
+    }
 }
 
-impl From<PrologError> for SicstusRsError {
-    fn from(error: PrologError) -> Self {
-        SicstusRsError::InternalError(error)
+impl From<IsoError> for ErrorTerm {
+    fn from(error: IsoError) -> Self {
+        ErrorTerm::new(error)
+    }
+}
+
+impl From<Error> for ErrorTerm {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::PredicateNotFound(indicator) => {
+                ErrorTerm::existence_error("procedure", Term::String(indicator))
+            }
+            Error::MalformedIndicator(indicator) => {
+                ErrorTerm::domain_error("predicate_indicator", Term::String(indicator))
+            }
+            Error::InvalidName(name) => ErrorTerm::domain_error("atom_name", Term::String(name)),
+            Error::TypeError { expected, culprit } => {
+                ErrorTerm::new(IsoError::TypeError { expected, culprit })
+            }
+            Error::InstantiationError => ErrorTerm::new(IsoError::InstantiationError),
+            Error::Syntax { message, .. } => ErrorTerm::new(IsoError::SyntaxError(message)),
+            Error::ArgumentOutOfRange { index, .. } => {
+                let message = error.to_string();
+                ErrorTerm::domain_error("argument_index", Term::Integer(index as i64))
+                    .message(&message)
+            }
+            Error::ArityMismatch { found, .. } => {
+                let message = error.to_string();
+                ErrorTerm::domain_error("arity", Term::Integer(found as i64)).message(&message)
+            }
+            Error::Conversion(message) => {
+                ErrorTerm::new(IsoError::RepresentationError(String::from("conversion")))
+                    .message(&message)
+            }
+            Error::InteriorNul(e) => {
+                let message = e.to_string();
+                let text = String::from_utf8_lossy(&e.into_vec()).into_owned();
+                ErrorTerm::domain_error("text_without_nul", Term::String(text)).message(&message)
+            }
+            Error::QueryClosed | Error::QueryNotInnermost => {
+                let message = error.to_string();
+                let reason = match error {
+                    Error::QueryClosed => "closed",
+                    _ => "not_innermost",
+                };
+                ErrorTerm::new(IsoError::PermissionError {
+                    action: String::from("access"),
+                    object_type: String::from("query"),
+                    culprit: Term::Atom(String::from(reason)),
+                })
+                .message(&message)
+            }
+            error => ErrorTerm::system_error().message(&error.to_string()),
+        }
     }
 }
@@ -0,0 +1,262 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{error::Error, sys, Term, TermRef, TermScope};
+
+/// The formal part of an ISO error term `error(Formal, Context)`.
+#[derive(Debug)]
+pub enum IsoError {
+    InstantiationError,
+    TypeError {
+        expected: String,
+        culprit: Term,
+    },
+    DomainError {
+        domain: String,
+        culprit: Term,
+    },
+    ExistenceError {
+        object_type: String,
+        culprit: Term,
+    },
+    PermissionError {
+        action: String,
+        object_type: String,
+        culprit: Term,
+    },
+    RepresentationError(String),
+    EvaluationError(String),
+    ResourceError(String),
+    SyntaxError(String),
+    SystemError,
+}
+
+impl IsoError {
+    /// Build the formal term of the error, e.g. `type_error(integer, foo)`.
+    pub fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let term = match self {
+            IsoError::InstantiationError => atom_term(scope, "instantiation_error")?,
+            IsoError::TypeError { expected, culprit } => compound_term(
+                scope,
+                "type_error",
+                &[atom_term(scope, expected)?, culprit.to_term_ref(scope)?],
+            )?,
+            IsoError::DomainError { domain, culprit } => compound_term(
+                scope,
+                "domain_error",
+                &[atom_term(scope, domain)?, culprit.to_term_ref(scope)?],
+            )?,
+            IsoError::ExistenceError {
+                object_type,
+                culprit,
+            } => compound_term(
+                scope,
+                "existence_error",
+                &[atom_term(scope, object_type)?, culprit.to_term_ref(scope)?],
+            )?,
+            IsoError::PermissionError {
+                action,
+                object_type,
+                culprit,
+            } => compound_term(
+                scope,
+                "permission_error",
+                &[
+                    atom_term(scope, action)?,
+                    atom_term(scope, object_type)?,
+                    culprit.to_term_ref(scope)?,
+                ],
+            )?,
+            IsoError::RepresentationError(flag) => {
+                compound_term(scope, "representation_error", &[atom_term(scope, flag)?])?
+            }
+            IsoError::EvaluationError(error) => {
+                compound_term(scope, "evaluation_error", &[atom_term(scope, error)?])?
+            }
+            IsoError::ResourceError(resource) => {
+                compound_term(scope, "resource_error", &[atom_term(scope, resource)?])?
+            }
+            IsoError::SyntaxError(message) => {
+                compound_term(scope, "syntax_error", &[atom_term(scope, message)?])?
+            }
+            IsoError::SystemError => atom_term(scope, "system_error")?,
+        };
+        Ok(term)
+    }
+}
+
+pub(crate) fn atom_term<'s>(scope: TermScope<'s>, name: &str) -> Result<TermRef<'s>, Error> {
+    let mut term = TermRef::new(scope);
+    term.put_atom(sys::sp_atom_from_string(name)?)?;
+    Ok(term)
+}
+
+pub(crate) fn compound_term<'s>(
+    scope: TermScope<'s>,
+    name: &str,
+    args: &[TermRef<'_>],
+) -> Result<TermRef<'s>, Error> {
+    let name = sys::sp_atom_from_string(name)?;
+    let args: Vec<_> = args.iter().map(|arg| arg.term_ref()).collect();
+    Ok(scope.adopt(sys::sp_cons_functor(name, &args)?))
+}
+
+/// An exception raised by Prolog.
+///
+/// Exceptions of the form `error(Formal, Context)` with one of the ISO formal terms are decoded into an [IsoError].
+/// The original exception term is always kept, so custom exceptions can still be inspected.
+/// Both are boxed to keep [Error] small.
+#[derive(Debug)]
+pub struct PrologException {
+    pub(crate) term: Box<Term>,
+    pub(crate) error: Option<Box<IsoError>>,
+}
+
+impl PrologException {
+    /// Retract the pending exception and decode it.
+    /// Returns None if there is no pending exception.
+    ///
+    /// This is typically called after a SICStus API function returned SP_ERROR.
+    pub fn take_pending() -> Option<Self> {
+        let term = sys::sp_exception_term().ok()??;
+        TermScope::run(|scope| {
+            // The term ref was just created, so it outlives the scope.
+            PrologException::from_term(&unsafe { TermRef::from_raw(scope, term) }).ok()
+        })
+    }
+
+    /// Copy the exception term and decode it.
+    pub fn from_term(term: &TermRef) -> Result<Self, Error> {
+        Ok(PrologException::from(term.to_term()?))
+    }
+
+    /// The exception term as it was raised.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    /// The decoded ISO error, or None if the exception is not an ISO error term.
+    pub fn iso_error(&self) -> Option<&IsoError> {
+        self.error.as_deref()
+    }
+
+    /// The context of an ISO error term, i.e. the second argument of `error(Formal, Context)`.
+    pub fn context(&self) -> Option<&Term> {
+        self.error.as_ref()?;
+        match &*self.term {
+            Term::Compound { args, .. } => args.get(1),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the exception is not an ISO error term.
+    pub fn is_custom(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl From<Term> for PrologException {
+    fn from(term: Term) -> Self {
+        let error = decode_iso_error(&term).map(Box::new);
+        PrologException {
+            term: Box::new(term),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for IsoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsoError::InstantiationError => f.write_str("instantiation error"),
+            IsoError::TypeError { expected, .. } => write!(f, "type error: expected {}", expected),
+            IsoError::DomainError { domain, .. } => write!(f, "domain error: expected {}", domain),
+            IsoError::ExistenceError { object_type, .. } => {
+                write!(f, "existence error: {} does not exist", object_type)
+            }
+            IsoError::PermissionError {
+                action,
+                object_type,
+                ..
+            } => write!(f, "permission error: cannot {} {}", action, object_type),
+            IsoError::RepresentationError(flag) => write!(f, "representation error: {}", flag),
+            IsoError::EvaluationError(error) => write!(f, "evaluation error: {}", error),
+            IsoError::ResourceError(resource) => write!(f, "resource error: {}", resource),
+            IsoError::SyntaxError(message) => write!(f, "syntax error: {}", message),
+            IsoError::SystemError => f.write_str("system error"),
+        }
+    }
+}
+
+impl core::error::Error for IsoError {}
+
+impl fmt::Display for PrologException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => error.fmt(f),
+            None => f.write_str("custom exception"),
+        }
+    }
+}
+
+impl core::error::Error for PrologException {}
+
+/// Get the text of an atom or code list.
+fn text(term: &Term) -> Option<String> {
+    match term {
+        Term::Atom(name) => Some(name.clone()),
+        Term::String(text) => Some(text.clone()),
+        Term::List(codes) => codes
+            .iter()
+            .map(|code| match code {
+                Term::Integer(code) => u32::try_from(*code).ok().and_then(char::from_u32),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn decode_iso_error(term: &Term) -> Option<IsoError> {
+    let Term::Compound { name, args } = term else {
+        return None;
+    };
+    if name != "error" || args.len() != 2 {
+        return None;
+    }
+    let (name, formal_args) = match &args[0] {
+        Term::Atom(name) => (name.as_str(), &[][..]),
+        Term::Compound { name, args } => (name.as_str(), args.as_slice()),
+        _ => return None,
+    };
+    let arg = |i: usize| formal_args.get(i - 1).cloned();
+    let text_arg = |i: usize| formal_args.get(i - 1).and_then(text);
+    let error = match (name, formal_args.len()) {
+        ("instantiation_error", 0) => IsoError::InstantiationError,
+        ("type_error", 2) => IsoError::TypeError {
+            expected: text_arg(1)?,
+            culprit: arg(2)?,
+        },
+        ("domain_error", 2) => IsoError::DomainError {
+            domain: text_arg(1)?,
+            culprit: arg(2)?,
+        },
+        ("existence_error", 2) => IsoError::ExistenceError {
+            object_type: text_arg(1)?,
+            culprit: arg(2)?,
+        },
+        ("permission_error", 3) => IsoError::PermissionError {
+            action: text_arg(1)?,
+            object_type: text_arg(2)?,
+            culprit: arg(3)?,
+        },
+        ("representation_error", 1) => IsoError::RepresentationError(text_arg(1)?),
+        ("evaluation_error", 1) => IsoError::EvaluationError(text_arg(1)?),
+        ("resource_error", 1) => IsoError::ResourceError(text_arg(1)?),
+        // The message of a syntax error is not always text, so we do not insist on it.
+        ("syntax_error", 1) => IsoError::SyntaxError(text_arg(1).unwrap_or_default()),
+        ("system_error", 0 | 1) => IsoError::SystemError,
+        _ => return None,
+    };
+    Some(error)
+}
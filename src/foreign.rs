@@ -0,0 +1,224 @@
+//! Foreign predicates implemented in Rust.
+//!
+//! [define_predicate] defines a predicate from a closure at runtime.
+//! The [foreign](crate::foreign) attribute, with the `derive` feature, turns a function into a foreign function
+//! that `splfr` links into a foreign resource.
+//!
+//! The module needs the `std` feature, since a panic must be caught before it unwinds into SICStus.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::ffi::{c_int, c_void};
+
+use crate::{
+    error::{handle_error, Error, ErrorTerm},
+    sys::{self, SP_term_ref, SP_FAILURE, SP_SUCCESS},
+    TermRef, TermScope,
+};
+
+type PredicateFn = dyn for<'s> Fn(&[TermRef<'s>]) -> Result<bool, Error> + 'static;
+
+/// What SICStus passes back to [trampoline] on every call.
+struct Stash {
+    name: String,
+    arity: usize,
+    function: Box<PredicateFn>,
+}
+
+/// Define the Prolog predicate `module:name/arity`, implemented by `function`.
+///
+/// The closure gets the arguments of the call. `Ok(true)` makes the call succeed and `Ok(false)` fail.
+/// An `Err` is raised as a Prolog exception, see [handle_error].
+/// The predicate is deterministic, i.e. it succeeds at most once.
+/// The term refs of a call are reclaimed when it returns, so new terms are built in the scope of the arguments.
+///
+/// ```ignore
+/// define_predicate("user", "rust_add", 3, |args| {
+///     let sum = i64::from_term(&args[0])? + i64::from_term(&args[1])?;
+///     args[2].unify(&sum.to_term(args[2].scope())?)?;
+///     Ok(true)
+/// })?;
+/// ```
+///
+/// A panic in the closure is caught and raised as a `system_error`.
+///
+/// Defining a predicate again replaces it, but the old closure is never dropped.
+pub fn define_predicate<F>(module: &str, name: &str, arity: usize, function: F) -> Result<(), Error>
+where
+    F: for<'s> Fn(&[TermRef<'s>]) -> Result<bool, Error> + 'static,
+{
+    let stash = Box::into_raw(Box::new(Stash {
+        name: String::from(name),
+        arity,
+        function: Box::new(function),
+    }));
+    let result =
+        sys::sp_define_c_predicate(name, arity, module, Some(trampoline), stash as *mut c_void);
+    if result.is_err() {
+        // SICStus did not keep the stash.
+        drop(unsafe { Box::from_raw(stash) });
+    }
+    result
+}
+
+unsafe extern "C" fn trampoline(goal: SP_term_ref, stash: *mut c_void) -> c_int {
+    // The stash was created by define_predicate and is never freed.
+    let stash = &*(stash as *const Stash);
+    let result = TermScope::run(|scope| {
+        let args: Result<Vec<TermRef>, Error> = (1..=stash.arity)
+            .map(|i| sys::sp_get_arg(i, goal).map(|arg| scope.adopt(arg)))
+            .collect();
+        match args {
+            Ok(args) => catch(&stash.name, stash.arity, || (stash.function)(&args)),
+            Err(e) => Ok(Err(e)),
+        }
+    });
+    complete(&stash.name, stash.arity, result)
+}
+
+/// Turn the outcome of a foreign call into what SICStus expects, raising errors and panics as exceptions.
+fn complete(name: &str, arity: usize, result: Result<Result<bool, Error>, String>) -> c_int {
+    match result {
+        Ok(Ok(true)) => SP_SUCCESS as c_int,
+        Ok(Ok(false)) => {
+            sys::sp_fail();
+            SP_FAILURE as c_int
+        }
+        Ok(Err(error @ Error::Exception { .. })) => {
+            handle_error(error);
+            SP_FAILURE as c_int
+        }
+        Ok(Err(error)) => {
+            ErrorTerm::from(error).predicate(name, arity).raise();
+            SP_FAILURE as c_int
+        }
+        Err(message) => {
+            ErrorTerm::system_error()
+                .predicate(name, arity)
+                .message(&message)
+                .raise();
+            SP_FAILURE as c_int
+        }
+    }
+}
+
+/// Call `f`, catching panics as their message.
+fn catch<R>(name: &str, arity: usize, f: impl FnOnce() -> R) -> Result<R, String> {
+    use alloc::{format, string::ToString};
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"));
+        format!("panic in {}/{}: {}", name, arity, message)
+    })
+}
+
+/// Used by the code generated by the [foreign](crate::foreign) attribute.
+#[doc(hidden)]
+pub mod __private {
+    use core::ffi::c_char;
+
+    use super::{catch, complete};
+    use crate::{
+        error::Error,
+        sys::{self, SP_atom, SP_integer, SP_term_ref},
+        util::string_from_ref,
+        Atom, FromTerm, TermRef, TermScope, ToTerm,
+    };
+
+    /// Run the body of a foreign function in a scope, raising errors and panics as exceptions.
+    pub fn call<F>(name: &str, arity: usize, body: F)
+    where
+        F: for<'s> FnOnce(TermScope<'s>) -> Result<(), Error>,
+    {
+        let result = catch(name, arity, || {
+            TermScope::run(|scope| body(scope).map(|()| true))
+        });
+        complete(name, arity, result);
+    }
+
+    pub fn integer(scope: TermScope<'_>, value: SP_integer) -> Result<TermRef<'_>, Error> {
+        Ok(TermRef::new_integer(scope, value))
+    }
+
+    pub fn float(scope: TermScope<'_>, value: f64) -> Result<TermRef<'_>, Error> {
+        Ok(TermRef::new_float(scope, value))
+    }
+
+    pub fn atom(scope: TermScope<'_>, value: SP_atom) -> Result<TermRef<'_>, Error> {
+        let mut term = TermRef::new(scope);
+        term.put_atom(value)?;
+        Ok(term)
+    }
+
+    /// # Safety
+    /// `value` must be a null terminated string.
+    pub unsafe fn string(scope: TermScope<'_>, value: *const c_char) -> Result<TermRef<'_>, Error> {
+        let mut term = TermRef::new(scope);
+        term.put_string(&string_from_ref(value))?;
+        Ok(term)
+    }
+
+    pub fn term(scope: TermScope<'_>, value: SP_term_ref) -> Result<TermRef<'_>, Error> {
+        // The glue code passes term refs of the current foreign call, which outlive the scope.
+        Ok(unsafe { TermRef::from_raw(scope, value) })
+    }
+
+    /// # Safety
+    /// `out` must be valid for writes.
+    pub unsafe fn put_integer<T: ToTerm>(
+        scope: TermScope<'_>,
+        out: *mut SP_integer,
+        value: &T,
+    ) -> Result<(), Error> {
+        *out = i64::from_term(&value.to_term(scope)?)?;
+        Ok(())
+    }
+
+    /// # Safety
+    /// `out` must be valid for writes.
+    pub unsafe fn put_float<T: ToTerm>(
+        scope: TermScope<'_>,
+        out: *mut f64,
+        value: &T,
+    ) -> Result<(), Error> {
+        *out = f64::from_term(&value.to_term(scope)?)?;
+        Ok(())
+    }
+
+    /// # Safety
+    /// `out` must be valid for writes.
+    pub unsafe fn put_atom<T: ToTerm>(
+        scope: TermScope<'_>,
+        out: *mut SP_atom,
+        value: &T,
+    ) -> Result<(), Error> {
+        *out = Atom::from_term(&value.to_term(scope)?)?.atom_id();
+        Ok(())
+    }
+
+    /// The string is the text of an atom, which stays valid until the glue code has copied it.
+    ///
+    /// # Safety
+    /// `out` must be valid for writes.
+    pub unsafe fn put_string<T: ToTerm>(
+        scope: TermScope<'_>,
+        out: *mut *const c_char,
+        value: &T,
+    ) -> Result<(), Error> {
+        let atom = Atom::from_term(&value.to_term(scope)?)?;
+        *out = sys::sp_string_from_atom_cstr(atom.atom_id()).as_ptr();
+        Ok(())
+    }
+
+    pub fn put_term<T: ToTerm>(
+        scope: TermScope<'_>,
+        out: SP_term_ref,
+        value: &T,
+    ) -> Result<(), Error> {
+        unsafe { TermRef::from_raw(scope, out) }.put_term(&value.to_term(scope)?)
+    }
+}
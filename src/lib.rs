@@ -3,8 +3,16 @@
 
 extern crate alloc;
 extern crate spin;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub mod compound;
+pub mod convert;
 pub mod error;
+mod exception;
+#[cfg(feature = "std")]
+pub mod foreign;
+mod list;
 
 pub mod sys;
 
@@ -12,21 +20,49 @@ pub mod sys;
 mod allocator;
 
 mod atom;
-#[macro_use]
+#[cfg(feature = "serde")]
+pub mod de;
+mod parse;
 mod query;
+mod runtime;
+mod scope;
+#[cfg(feature = "serde")]
+pub mod ser;
+mod term;
 mod term_ref;
 mod util;
+mod view;
+mod write;
 
 use core::fmt::Write;
 
 use alloc::fmt;
 use spin::Mutex;
 pub use sys::{SP_atom, SP_integer, SP_pred_ref, SP_term_ref};
+pub use term::Term;
 pub use term_ref::TermRef;
+pub use view::{Args, TermType, TermView};
+pub use write::WriteOptions;
 
 pub use atom::Atom;
-pub use error::SicstusRsError;
-pub use query::Predicate;
+pub use compound::Compound;
+pub use convert::{FromTerm, ToTerm};
+pub use error::{ApiError, Error, ErrorTerm};
+pub use exception::{IsoError, PrologException};
+#[cfg(feature = "std")]
+pub use foreign::define_predicate;
+pub use list::{ListIter, PrologList};
+pub use parse::Bindings;
+pub use query::{Predicate, PredicateIndicator, Query, Solutions};
+pub use runtime::{FileName, LoadOptions, Runtime, RuntimeOptions};
+pub use scope::TermScope;
+#[cfg(feature = "derive")]
+pub use sicstus_rs_derive::{foreign, FromTerm, ToTerm};
+#[cfg(feature = "serde")]
+pub use {
+    de::from_term,
+    ser::{to_term, to_term_with_config, Config, StringStyle},
+};
 
 pub static WRITER: Mutex<Writer> = Mutex::new(Writer {});
 
@@ -0,0 +1,143 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::{
+    convert::{is_nil, type_error},
+    error::Error,
+    exception::atom_term,
+    sys, TermRef, TermScope,
+};
+
+/// A builder for Prolog lists that keeps the order of its items.
+///
+/// ```ignore
+/// let list = PrologList::new().push(a).push(b).build(scope)?;             // [A, B]
+/// let partial = PrologList::from(items).tail(rest).build(scope)?;          // [A, B | Rest]
+/// let (list, hole) = PrologList::from(items).build_difference(scope)?;     // [A, B | Hole]-Hole
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PrologList<'s> {
+    items: Vec<TermRef<'s>>,
+    tail: Option<TermRef<'s>>,
+}
+
+impl<'s> PrologList<'s> {
+    pub fn new() -> Self {
+        PrologList::default()
+    }
+
+    /// Append an item at the end.
+    pub fn push(mut self, item: TermRef<'s>) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// End the list in `tail` instead of `[]`.
+    pub fn tail(mut self, tail: TermRef<'s>) -> Self {
+        self.tail = Some(tail);
+        self
+    }
+
+    /// The number of items, not counting the tail.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Build the list on the Prolog heap.
+    pub fn build(self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let list = match self.tail {
+            Some(tail) => tail,
+            None => atom_term(scope, "[]")?,
+        };
+        // SP_cons_list may write its result into the tail argument.
+        for item in self.items.into_iter().rev() {
+            sys::sp_cons_list(list.term_ref(), item.term_ref(), list.term_ref())?;
+        }
+        Ok(list)
+    }
+
+    /// Build the list ending in a fresh variable and return it along with that variable.
+    /// Together they form the difference list `List-Hole`.
+    pub fn build_difference(
+        self,
+        scope: TermScope<'s>,
+    ) -> Result<(TermRef<'s>, TermRef<'s>), Error> {
+        let hole = TermRef::new_variable(scope);
+        let list = self.tail(hole.clone()).build(scope)?;
+        Ok((list, hole))
+    }
+}
+
+impl<'s> From<Vec<TermRef<'s>>> for PrologList<'s> {
+    fn from(items: Vec<TermRef<'s>>) -> Self {
+        PrologList { items, tail: None }
+    }
+}
+
+impl<'s> FromIterator<TermRef<'s>> for PrologList<'s> {
+    fn from_iter<I: IntoIterator<Item = TermRef<'s>>>(iter: I) -> Self {
+        PrologList::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<'s> Extend<TermRef<'s>> for PrologList<'s> {
+    fn extend<I: IntoIterator<Item = TermRef<'s>>>(&mut self, iter: I) {
+        self.items.extend(iter);
+    }
+}
+
+impl<'s> TermRef<'s> {
+    /// Iterate over the elements of a list.
+    ///
+    /// After the last element, a partial list like `[a | _]` yields [Error::InstantiationError],
+    /// and an improper list like `[a | b]` yields a type error.
+    pub fn list_iter(&self) -> ListIter<'s> {
+        ListIter {
+            list: self.clone(),
+            rest: Some(self.clone()),
+        }
+    }
+
+    /// The number of elements of a proper list.
+    pub fn list_len(&self) -> Result<usize, Error> {
+        self.list_iter()
+            .try_fold(0, |len, item| item.map(|_| len + 1))
+    }
+
+    /// Whether the term is a list ending in `[]`.
+    pub fn is_proper_list(&self) -> bool {
+        self.list_len().is_ok()
+    }
+}
+
+/// The iterator returned by [TermRef::list_iter].
+#[derive(Debug, Clone)]
+pub struct ListIter<'s> {
+    list: TermRef<'s>,
+    /// The unvisited part of the list, None once the end or an error was reached.
+    rest: Option<TermRef<'s>>,
+}
+
+impl<'s> Iterator for ListIter<'s> {
+    type Item = Result<TermRef<'s>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.take()?;
+        if let Some((head, tail)) = rest.get_list() {
+            self.rest = Some(tail);
+            Some(Ok(head))
+        } else if is_nil(&rest) {
+            None
+        } else if rest.is_variable() {
+            Some(Err(Error::InstantiationError))
+        } else {
+            Some(Err(type_error("list", &self.list)))
+        }
+    }
+}
+
+impl FusedIterator for ListIter<'_> {}
@@ -0,0 +1,130 @@
+use alloc::{collections::BTreeMap, format, string::String};
+
+use crate::{
+    error::Error, write::load_codesio, Compound, IsoError, Predicate, PrologException, Term,
+    TermRef, TermScope, ToTerm,
+};
+
+/// The variables of a parsed term, by name.
+pub type Bindings<'s> = BTreeMap<String, TermRef<'s>>;
+
+impl<'s> TermRef<'s> {
+    /// Read a term from Prolog text like `"foo(X, Y, bar)"`.
+    /// The terminating full stop is optional.
+    ///
+    /// Returns the term and its named variables. Anonymous variables `_` are not included.
+    /// Syntax errors are returned as [Error::Syntax].
+    pub fn parse(scope: TermScope<'s>, text: &str) -> Result<(TermRef<'s>, Bindings<'s>), Error> {
+        TermRef::parse_with(scope, text, &[])
+    }
+
+    /// Like [TermRef::parse], but the variables named in `bindings` are bound to the given terms.
+    /// It is an error to bind a variable that does not occur in the text.
+    pub fn parse_with(
+        scope: TermScope<'s>,
+        text: &str,
+        bindings: &[(&str, &TermRef<'s>)],
+    ) -> Result<(TermRef<'s>, Bindings<'s>), Error> {
+        let mut source = String::from(text.trim_end());
+        if !source.ends_with('.') {
+            source.push_str(" .");
+        }
+        let (term, mut variables) = match read_term(scope, &source) {
+            Err(Error::Exception {
+                function,
+                exception,
+            }) => Err(syntax_error(text, &exception).unwrap_or(Error::Exception {
+                function,
+                exception,
+            })),
+            result => result,
+        }?;
+        for (name, value) in bindings {
+            let variable = variables.get_mut(*name).ok_or_else(|| {
+                Error::Conversion(format!("variable {} does not occur in {}", name, text))
+            })?;
+            variable.unify(value)?;
+            *variable = (*value).clone();
+        }
+        Ok((term, variables))
+    }
+
+    /// Create a prolog term from a string containing prolog code.
+    pub fn from_prolog(scope: TermScope<'s>, prolog_code: &str) -> Result<Self, Error> {
+        TermRef::parse(scope, prolog_code).map(|(term, _)| term)
+    }
+}
+
+/// Decode a syntax error exception into [Error::Syntax].
+/// Returns None if the exception is not a syntax error or has no position.
+fn syntax_error(text: &str, exception: &PrologException) -> Option<Error> {
+    let message = match exception.iso_error()? {
+        IsoError::SyntaxError(message) => message.clone(),
+        _ => return None,
+    };
+    let offset = error_position(exception.context()?)?;
+    let (line, column) = line_column(text, usize::try_from(offset).ok()?);
+    Some(Error::Syntax {
+        message,
+        line,
+        column,
+    })
+}
+
+/// The character offset of a syntax error, from the error context
+/// `syntax_error(Goal, Position, Message, Tokens, AfterError)` that SICStus raises it with.
+/// Returns None for any other context.
+fn error_position(context: &Term) -> Option<i64> {
+    match context {
+        Term::Compound { name, args } if name == "syntax_error" && args.len() == 5 => {
+            match args[1] {
+                Term::Integer(offset) => Some(offset),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The 1-based line and column of a character offset.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for c in text.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// Read a term terminated by a full stop with `read_term/2`, which reports exactly which variable has which name.
+fn read_term<'s>(scope: TermScope<'s>, source: &str) -> Result<(TermRef<'s>, Bindings<'s>), Error> {
+    load_codesio()?;
+    let mut codes = TermRef::new(scope);
+    codes.put_list_codes(&TermRef::new(scope), source)?;
+    let term = TermRef::new_variable(scope);
+    let names = TermRef::new_variable(scope);
+    let options = [Compound::new("variable_names").arg(names.clone())].to_term(scope)?;
+    let read_term_from_codes = Predicate::new("codesio", "read_term_from_codes", 3)?;
+    if !read_term_from_codes.call_once(&[codes, term.clone(), options])? {
+        return Err(Error::Conversion(format!("could not read {}", source)));
+    }
+    let mut variables = Bindings::new();
+    for binding in names.list_iter() {
+        // Each binding is Name = Variable.
+        let binding = binding?;
+        variables.insert(binding.arg::<String>(1)?, binding.get_arg(2)?);
+    }
+    Ok((term, variables))
+}
+
+#[cfg(test)]
+#[test]
+fn test_line_column() {
+    assert_eq!(line_column("foo(", 4), (1, 5));
+    assert_eq!(line_column("a,\n b(", 5), (2, 3));
+}
@@ -1,31 +1,364 @@
-use crate::{sys::PrologError, Atom};
-use alloc::string::String;
-use sicstus_sys::SP_pred;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::SP_pred_ref;
+use crate::{error::Error, scope, sys, TermRef};
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use sicstus_sys::{SP_atom, SP_qid, SP_term_ref};
+use spin::Mutex;
 
+/// The largest arity SICStus Prolog supports for compound terms and predicates.
+const MAX_ARITY: usize = 255;
 
-pub enum QueryError {
-    InternalError(PrologError),
+/// A parsed Prolog predicate indicator like `lists:append/3`.
+///
+/// The module is optional and defaults to `user`. Module and name may be quoted, as in `'my pred'/2`,
+/// but quoted atoms must not contain escaped quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PredicateIndicator<'a> {
+    pub module: &'a str,
+    pub name: &'a str,
+    pub arity: usize,
 }
 
-impl From<PrologError> for QueryError {
-    fn from(error: PrologError) -> Self {
-        QueryError::InternalError(error)
+impl<'a> PredicateIndicator<'a> {
+    pub fn parse(indicator: &'a str) -> Result<Self, Error> {
+        let malformed = || Error::MalformedIndicator(indicator.to_string());
+        let (spec, arity) = indicator.trim().rsplit_once('/').ok_or_else(malformed)?;
+        let arity: usize = arity.trim().parse().map_err(|_| malformed())?;
+        if arity > MAX_ARITY {
+            return Err(malformed());
+        }
+        let (module, name) = match split_module(spec.trim()).ok_or_else(malformed)? {
+            (Some(module), name) => (module, name),
+            (None, name) => ("user", name),
+        };
+        Ok(PredicateIndicator {
+            module: unquote(module).ok_or_else(malformed)?,
+            name: unquote(name).ok_or_else(malformed)?,
+            arity,
+        })
+    }
+}
+
+/// Split `Module:Name` at the first colon that is not inside a quoted atom.
+/// Returns None if the quotes are unbalanced.
+fn split_module(spec: &str) -> Option<(Option<&str>, &str)> {
+    let mut quoted = false;
+    for (i, c) in spec.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            ':' if !quoted && i > 0 => return Some((Some(spec[..i].trim()), spec[i + 1..].trim())),
+            _ => {}
+        }
+    }
+    if quoted {
+        None
+    } else {
+        Some((None, spec))
+    }
+}
+
+/// Strip the quotes of a quoted atom. Returns None for empty names and misplaced quotes.
+fn unquote(atom: &str) -> Option<&str> {
+    let atom = match atom.strip_prefix('\'') {
+        Some(rest) => rest.strip_suffix('\'')?,
+        None => atom,
+    };
+    if atom.is_empty() || atom.contains('\'') {
+        None
+    } else {
+        Some(atom)
     }
 }
 
 pub struct Predicate {
-    _pred_ref: SP_pred_ref,
+    pred_ref: SP_pred_ref,
+    module: SP_atom,
+    name: SP_atom,
+    arity: usize,
 }
 
 impl Predicate {
-    pub fn new(module: String, name: String, arity: usize) -> Result<Self, QueryError> {
-        let module = Atom::from_string(module);
-        let name = Atom::from_string(name);
-        let pred_ref = unsafe { SP_pred(name.as_atom(), arity as i64, module.as_atom()) };
-        if pred_ref.is_null() {
-            return Err(QueryError::InternalError(PrologError::PredicateNotFound));
-        }
-        Ok(Predicate { _pred_ref: pred_ref })
+    /// Look up the predicate module:name/arity.
+    ///
+    /// The atoms of module and name do not need to be registered,
+    /// because an existing predicate keeps them alive.
+    pub fn new(module: &str, name: &str, arity: usize) -> Result<Self, Error> {
+        if arity > MAX_ARITY {
+            return Err(Error::PredicateNotFound(format!(
+                "{}:{}/{}",
+                module, name, arity
+            )));
+        }
+        let module_atom = sys::sp_atom_from_string(module)?;
+        let name_atom = sys::sp_atom_from_string(name)?;
+        let pred_ref = sys::sp_pred(name_atom, arity as u32, module_atom)?;
+        Ok(Predicate {
+            pred_ref,
+            module: module_atom,
+            name: name_atom,
+            arity,
+        })
+    }
+
+    /// Look up a predicate from a predicate indicator like `lists:append/3` or `foo/2`.
+    /// Indicators without a module refer to the `user` module.
+    pub fn from_indicator(indicator: &str) -> Result<Self, Error> {
+        let indicator = PredicateIndicator::parse(indicator)?;
+        Predicate::new(indicator.module, indicator.name, indicator.arity)
+    }
+
+    pub fn pred_ref(&self) -> SP_pred_ref {
+        self.pred_ref
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Call the predicate once, keeping the bindings of the first solution in the arguments.
+    ///
+    /// # Returns
+    /// Ok(true) if the call succeeded, Ok(false) if it failed, and Err if it raised an exception.
+    pub fn call_once(&self, args: &[TermRef<'_>]) -> Result<bool, Error> {
+        sys::sp_query(self.goal(args)?)
+    }
+
+    /// Call the predicate once for its side effects. Any bindings are undone afterwards.
+    ///
+    /// # Returns
+    /// Ok(true) if the call succeeded, Ok(false) if it failed, and Err if it raised an exception.
+    pub fn call_for_side_effects(&self, args: &[TermRef<'_>]) -> Result<bool, Error> {
+        sys::sp_query_cut_fail(self.goal(args)?)
+    }
+
+    /// Call the predicate once and return the arguments bound to its first solution,
+    /// or None if the call failed.
+    pub fn call<'s>(&self, args: &[TermRef<'s>]) -> Result<Option<Vec<TermRef<'s>>>, Error> {
+        if self.call_once(args)? {
+            Ok(Some(
+                args.iter().map(|arg| arg.in_scope(arg.scope())).collect(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Build the module qualified goal `Module:Name(Args...)` for a call to this predicate.
+    fn goal(&self, args: &[TermRef<'_>]) -> Result<SP_term_ref, Error> {
+        if args.len() != self.arity {
+            return Err(Error::ArityMismatch {
+                expected: self.arity,
+                found: args.len(),
+            });
+        }
+        let args: Vec<SP_term_ref> = args.iter().map(|arg| arg.term_ref()).collect();
+        let goal = sys::sp_cons_functor(self.name, &args)?;
+        let module = sys::sp_new_term_ref();
+        sys::sp_put_atom(module, self.module)?;
+        let colon = sys::sp_atom_from_string(":")?;
+        sys::sp_cons_functor(colon, &[module, goal])
+    }
+}
+
+/// The ids of all queries that are currently open, innermost last.
+static OPEN_QUERIES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+static NEXT_QUERY_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An open Prolog query, that can be asked for solutions one by one.
+///
+/// Prolog requires queries to be strictly nested: only the innermost open query can be asked for
+/// another solution, and terminating a query also terminates all queries opened after it.
+/// Query keeps track of this, so operations on a query that was already terminated by an enclosing
+/// one are rejected instead of being passed on to Prolog.
+///
+/// When a Query is dropped it is cut, which keeps the bindings of the current solution.
+/// Use [Query::close] to discard them instead.
+///
+/// A query can not outlive the [TermScope](crate::TermScope) of its arguments.
+pub struct Query<'s> {
+    qid: SP_qid,
+    id: usize,
+    args: Vec<TermRef<'s>>,
+}
+
+impl<'s> Query<'s> {
+    /// Open a query for the given predicate.
+    ///
+    /// # Arguments
+    /// * predicate - The predicate to call.
+    /// * args - The arguments, one for each argument of the predicate.
+    ///   Arguments that should be bound by the query must be unbound variables, see [TermRef::new_variable].
+    pub fn open(predicate: &Predicate, args: &[TermRef<'s>]) -> Result<Self, Error> {
+        let goal = predicate.goal(args)?;
+        let qid = sys::sp_open_query(goal)?;
+        // The query may outlive the scopes nested in the one of its arguments,
+        // which must then leave the term refs of the goal alone.
+        scope::pin(
+            args.iter()
+                .map(|arg| arg.scope().depth())
+                .max()
+                .unwrap_or(0),
+        );
+        let id = NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed);
+        OPEN_QUERIES.lock().push(id);
+        Ok(Query {
+            qid,
+            id,
+            args: args.iter().map(|arg| arg.in_scope(arg.scope())).collect(),
+        })
+    }
+
+    /// Look for the next solution of the query.
+    ///
+    /// Returns Err(Error::NoMoreSolutions) if there are no more solutions.
+    /// The bindings of the previous solution are undone.
+    pub fn next_solution(&mut self) -> Result<(), Error> {
+        {
+            let open_queries = OPEN_QUERIES.lock();
+            match open_queries.iter().position(|&id| id == self.id) {
+                None => return Err(Error::QueryClosed),
+                Some(i) if i + 1 != open_queries.len() => return Err(Error::QueryNotInnermost),
+                Some(_) => {}
+            }
+        }
+        sys::sp_next_solution(self.qid)
+    }
+
+    /// The arguments the query was opened with.
+    /// After a successful [Query::next_solution] they hold the bindings of the current solution.
+    pub fn args(&self) -> Vec<TermRef<'s>> {
+        self.args
+            .iter()
+            .map(|arg| arg.in_scope(arg.scope()))
+            .collect()
+    }
+
+    pub fn is_open(&self) -> bool {
+        OPEN_QUERIES.lock().contains(&self.id)
+    }
+
+    /// Turn the query into an iterator over its solutions.
+    pub fn solutions(self) -> Solutions<'s> {
+        Solutions {
+            query: self,
+            remaining: None,
+            done: false,
+        }
+    }
+
+    /// Commit to the current solution and close the query, keeping its bindings.
+    pub fn cut(mut self) -> Result<(), Error> {
+        self.terminate(sys::sp_cut_query)
+    }
+
+    /// Discard the current solution and close the query.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.terminate(sys::sp_close_query)
+    }
+
+    /// Terminate the query with the given function, unless an enclosing query already did.
+    /// Any queries opened inside this one are terminated by Prolog as well.
+    fn terminate(&mut self, terminate: fn(SP_qid) -> Result<(), Error>) -> Result<(), Error> {
+        let position = {
+            let mut open_queries = OPEN_QUERIES.lock();
+            let position = open_queries.iter().position(|&id| id == self.id);
+            if let Some(i) = position {
+                open_queries.truncate(i);
+            }
+            position
+        };
+        match position {
+            Some(_) => terminate(self.qid),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Query<'_> {
+    fn drop(&mut self) {
+        let _ = self.terminate(sys::sp_cut_query);
+    }
+}
+
+/// An iterator over the solutions of a [Query].
+///
+/// Each item holds the arguments of the query, bound to the values of that solution.
+/// The bindings are undone when the next solution is requested, so read what you need from them first.
+/// An exception raised by the query is returned as an Err item, after which the iterator ends.
+/// Dropping the iterator cuts the query.
+pub struct Solutions<'s> {
+    query: Query<'s>,
+    remaining: Option<usize>,
+    done: bool,
+}
+
+impl Solutions<'_> {
+    /// Limit the iterator to at most n solutions.
+    ///
+    /// Unlike [Iterator::take], the query is cut as soon as the n-th solution is found,
+    /// instead of when the iterator is dropped. The bindings of that last solution are kept.
+    pub fn take(mut self, n: usize) -> Self {
+        self.remaining = Some(n);
+        self
+    }
+}
+
+impl<'s> Iterator for Solutions<'s> {
+    type Item = Result<Vec<TermRef<'s>>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.remaining == Some(0) {
+            self.done = true;
+            return self.query.terminate(sys::sp_cut_query).err().map(Err);
+        }
+        match self.query.next_solution() {
+            Ok(()) => {
+                if let Some(remaining) = self.remaining.as_mut() {
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        self.done = true;
+                        if let Err(e) = self.query.terminate(sys::sp_cut_query) {
+                            return Some(Err(e));
+                        }
+                    }
+                }
+                Some(Ok(self.query.args()))
+            }
+            Err(Error::NoMoreSolutions) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_predicate_indicator() {
+    let parse = |indicator| {
+        let parsed = PredicateIndicator::parse(indicator).unwrap();
+        (parsed.module, parsed.name, parsed.arity)
+    };
+    assert_eq!(parse("lists:append/3"), ("lists", "append", 3));
+    assert_eq!(parse("foo/2"), ("user", "foo", 2));
+    assert_eq!(parse("'my mod':'a:b'/0"), ("my mod", "a:b", 0));
+    assert_eq!(parse("prolog:=../2"), ("prolog", "=..", 2));
+    for malformed in [
+        "foo", "foo/", "foo/x", "/2", "lists:/3", "'foo/1", "foo/256",
+    ] {
+        assert!(matches!(
+            PredicateIndicator::parse(malformed),
+            Err(Error::MalformedIndicator(_))
+        ));
     }
 }
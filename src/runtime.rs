@@ -0,0 +1,309 @@
+//! Embedding SICStus in a Rust program.
+
+use alloc::{ffi::CString, string::String, vec::Vec};
+use core::{ffi::c_char, ptr};
+
+use crate::{
+    error::Error, sys, util::to_cstring, write, Compound, Predicate, TermRef, TermScope, ToTerm,
+};
+
+/// Options for [Runtime::init].
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeOptions {
+    args: Vec<String>,
+    properties: Vec<(String, String)>,
+}
+
+impl RuntimeOptions {
+    pub fn new() -> Self {
+        RuntimeOptions::default()
+    }
+
+    /// Append a command line argument. Like in C, the first one is the program name.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(String::from(arg));
+        self
+    }
+
+    /// Append command line arguments, e.g. `std::env::args()`.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| String::from(arg.as_ref())));
+        self
+    }
+
+    /// Set a system property, e.g. `SP_ULIMIT_DATA_SEGMENT_SIZE`.
+    pub fn property(mut self, key: &str, value: &str) -> Self {
+        self.properties
+            .push((String::from(key), String::from(value)));
+        self
+    }
+}
+
+/// Options for [Runtime::load_files], which become the options list of `load_files/2`.
+///
+/// ```ignore
+/// runtime.load_files("src/main.pl", &LoadOptions::new().module("app").condition("not_loaded"))?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    module: Option<String>,
+    options: Vec<(String, String)>,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        LoadOptions::default()
+    }
+
+    /// Load the file into `module` instead of `user`, unless it is a module file.
+    pub fn module(mut self, module: &str) -> Self {
+        self.module = Some(String::from(module));
+        self
+    }
+
+    /// `if(Condition)`: load the file `true` (always), if `changed` (the default) or if `not_loaded`.
+    pub fn condition(self, condition: &str) -> Self {
+        self.option("if", condition)
+    }
+
+    /// `compilation_mode(Mode)`: `compile`, `consult` or `assert_all`.
+    pub fn compilation_mode(self, mode: &str) -> Self {
+        self.option("compilation_mode", mode)
+    }
+
+    /// `load_type(Type)`: `source`, `object` or `latest` (the default).
+    pub fn load_type(self, load_type: &str) -> Self {
+        self.option("load_type", load_type)
+    }
+
+    /// `encoding(Encoding)`, e.g. `UTF-8`.
+    pub fn encoding(self, encoding: &str) -> Self {
+        self.option("encoding", encoding)
+    }
+
+    /// Any other option `Name(Value)` whose value is an atom, e.g. `imports` and `all`.
+    pub fn option(mut self, name: &str, value: &str) -> Self {
+        self.options.push((String::from(name), String::from(value)));
+        self
+    }
+
+    /// The file argument of `load_files/2`, qualified with the module if there is one.
+    fn file<'s>(&self, scope: TermScope<'s>, file: &str) -> Result<TermRef<'s>, Error> {
+        match &self.module {
+            Some(module) => Compound::new(":")
+                .arg(module.as_str())
+                .arg(file)
+                .build(scope),
+            None => file.to_term(scope),
+        }
+    }
+
+    /// The options list of `load_files/2`, with `extra` options in front.
+    fn options<'s>(
+        &self,
+        scope: TermScope<'s>,
+        extra: Vec<Compound>,
+    ) -> Result<TermRef<'s>, Error> {
+        let options: Vec<Compound> = extra
+            .into_iter()
+            .chain(
+                self.options
+                    .iter()
+                    .map(|(name, value)| Compound::new(name).arg(value.as_str())),
+            )
+            .collect();
+        options.to_term(scope)
+    }
+}
+
+/// A file name that can be loaded: `str` and `String`, and with the `std` feature also `Path` and `PathBuf`.
+pub trait FileName {
+    /// The file name as text, which is what SICStus expects.
+    fn as_text(&self) -> Result<&str, Error>;
+}
+
+impl FileName for str {
+    fn as_text(&self) -> Result<&str, Error> {
+        Ok(self)
+    }
+}
+
+impl FileName for String {
+    fn as_text(&self) -> Result<&str, Error> {
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FileName for std::path::Path {
+    fn as_text(&self) -> Result<&str, Error> {
+        self.to_str().ok_or_else(|| {
+            Error::Conversion(alloc::format!(
+                "the file name {:?} is not valid UTF-8",
+                self
+            ))
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl FileName for std::path::PathBuf {
+    fn as_text(&self) -> Result<&str, Error> {
+        self.as_path().as_text()
+    }
+}
+
+/// The SICStus runtime, started by a Rust program that embeds Prolog.
+///
+/// Code loaded by Prolog as a foreign resource runs in a runtime that is already started and does not need this.
+/// The program must be linked against the runtime library `libsprt`, which sicstus-sys does, and find it at run
+/// time, e.g. with `sicstus_build::Sicstus::locate()?.link_runtime()` in its build script.
+///
+/// ```ignore
+/// let runtime = Runtime::init(RuntimeOptions::new().args(std::env::args()))?;
+/// runtime.load("src/main.pl")?;
+/// let pred = Predicate::new("user", "main", 0)?;
+/// // ...
+/// drop(runtime);
+/// ```
+///
+/// The runtime must be used from the thread that started it, so it is neither `Send` nor `Sync`.
+/// Dropping it stops the runtime, after which no other API function may be called.
+#[derive(Debug)]
+pub struct Runtime {
+    /// SICStus may keep pointers to the arguments.
+    _args: Vec<CString>,
+    argv: Vec<*mut c_char>,
+}
+
+impl Runtime {
+    /// Start the runtime and set the `argv` Prolog flag from the arguments in `options`.
+    pub fn init(options: RuntimeOptions) -> Result<Runtime, Error> {
+        if sys::sp_is_initialized() {
+            return Err(Error::AlreadyInitialized);
+        }
+        let args = options
+            .args
+            .iter()
+            .map(|arg| to_cstring(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+        let properties = options
+            .properties
+            .iter()
+            .map(|(key, value)| Ok((to_cstring(key)?, to_cstring(value)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut argv: Vec<*mut c_char> = args
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut c_char)
+            .chain([ptr::null_mut()])
+            .collect();
+
+        sys::sp_initialize(&mut argv, &properties)?;
+        // From here on dropping the runtime stops it again.
+        let mut runtime = Runtime { _args: args, argv };
+        sys::sp_set_argv(&mut runtime.argv)?;
+        Ok(runtime)
+    }
+
+    /// Load a Prolog source, `.po` or foreign resource file into the `user` module, like `load_files/1`.
+    pub fn load<F: FileName + ?Sized>(&self, file: &F) -> Result<(), Error> {
+        sys::sp_load(&expand(file)?)
+    }
+
+    /// Restore a saved state created with `save_program/1`, like `restore/1`.
+    /// This replaces all Prolog code and data, but keeps the runtime running.
+    pub fn restore<F: FileName + ?Sized>(&self, saved_state: &F) -> Result<(), Error> {
+        let restored = sys::sp_restore(&expand(saved_state)?);
+        write::forget_codesio();
+        restored
+    }
+
+    /// Load a file with `load_files/2` and the given options.
+    pub fn load_files<F: FileName + ?Sized>(
+        &self,
+        file: &F,
+        options: &LoadOptions,
+    ) -> Result<(), Error> {
+        let file = expand(file)?;
+        TermScope::run(|scope| {
+            let args = [
+                options.file(scope, &file)?,
+                options.options(scope, Vec::new())?,
+            ];
+            load_files(&file, &args)
+        })
+    }
+
+    /// Load Prolog clauses and directives from text, as if they were the contents of a file.
+    ///
+    /// ```ignore
+    /// runtime.consult_str("greeting(hello).\n:- initialization(main).\n")?;
+    /// ```
+    pub fn consult_str(&self, source: &str) -> Result<(), Error> {
+        self.consult_str_with(source, &LoadOptions::new())
+    }
+
+    /// Load Prolog text like [Runtime::consult_str], with the options of [Runtime::load_files].
+    pub fn consult_str_with(&self, source: &str, options: &LoadOptions) -> Result<(), Error> {
+        TermScope::run(|scope| {
+            let mut codes = TermRef::new(scope);
+            codes.put_list_codes(&TermRef::new(scope), source)?;
+            let stream = open_codes_stream(codes)?;
+            let loaded = load_stream(&stream, options);
+            Predicate::new("user", "close", 1)?.call_for_side_effects(&[stream])?;
+            loaded
+        })
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        sys::sp_deinitialize();
+        write::forget_codesio();
+    }
+}
+
+/// The absolute name of `file`, which is how SICStus refers to it in messages and `source_file/1`.
+fn expand<F: FileName + ?Sized>(file: &F) -> Result<String, Error> {
+    sys::sp_expand_file_name(file.as_text()?, 0)
+}
+
+/// Call `load_files/2`, turning failures and exceptions into [Error::Load].
+fn load_files(file: &str, args: &[TermRef<'_>]) -> Result<(), Error> {
+    let load_error = |exception| Error::Load {
+        file: String::from(file),
+        exception,
+    };
+    match Predicate::new("user", "load_files", 2)?.call_for_side_effects(args) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(load_error(None)),
+        Err(Error::Exception { exception, .. }) => Err(load_error(Some(exception))),
+        Err(error) => Err(error),
+    }
+}
+
+/// Load the text read from `stream`.
+fn load_stream(stream: &TermRef, options: &LoadOptions) -> Result<(), Error> {
+    // The name is only used in messages, since the text is read from the stream.
+    let name = "user_string";
+    let scope = stream.scope();
+    let stream_option = Compound::new("stream").arg(stream.clone());
+    let args = [
+        options.file(scope, name)?,
+        options.options(scope, Vec::from([stream_option]))?,
+    ];
+    load_files(name, &args)
+}
+
+/// Open a Prolog input stream that reads the code list `codes`.
+fn open_codes_stream(codes: TermRef<'_>) -> Result<TermRef<'_>, Error> {
+    write::load_codesio()?;
+    let stream = TermRef::new_variable(codes.scope());
+    Predicate::new("codesio", "open_codes_stream", 2)?.call_once(&[codes, stream.clone()])?;
+    Ok(stream)
+}
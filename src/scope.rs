@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use spin::Mutex;
+
+use crate::{
+    error::Error,
+    sys::{self, SP_term_ref},
+    TermRef,
+};
+
+/// The first term ref of every running scope, outermost first.
+static MARKS: Mutex<Vec<SP_term_ref>> = Mutex::new(Vec::new());
+
+/// Reclaims the term refs allocated inside a closure.
+///
+/// SICStus keeps every term ref until the foreign call that created it returns.
+/// Loops that create term refs, e.g. with [TermRef::new], [TermRef::get_arg] or [TermRef::list_iter],
+/// can run out of memory on large inputs. Running the loop body in a scope frees its term refs after each iteration:
+///
+/// ```ignore
+/// let mut sum = 0;
+/// for item in list.list_iter() {
+///     let item = item?;
+///     sum += TermScope::run(|scope| item.in_scope(scope).arg::<i64>(1))?;
+/// }
+/// ```
+///
+/// A scope is also the token every term ref is created with, e.g. `TermRef::new(scope)` or `42.to_term(scope)`.
+/// The term ref borrows the scope, so it can not be used after the scope reclaimed it.
+/// Term refs of an enclosing scope can be used inside, and new ones created through them or their scope stay
+/// alive until that scope ends. [TermRef::in_scope] moves the term refs created through a term to an inner scope,
+/// and [TermScope::run_term] copies a term out of one.
+#[derive(Debug, Clone, Copy)]
+pub struct TermScope<'s> {
+    /// The number of scopes this one is nested in, plus one.
+    depth: usize,
+    /// Covariant in `'s`, and neither `Send` nor `Sync` since SICStus is only called from one thread.
+    _scope: PhantomData<(&'s (), *const ())>,
+}
+
+impl TermScope<'_> {
+    /// Run `f` in a new scope and reclaim the term refs created in it when it returns or panics.
+    ///
+    /// The result can not hold term refs of the new scope. Use [TermScope::run_term] to keep a term.
+    pub fn run<R, F>(f: F) -> R
+    where
+        F: for<'t> FnOnce(TermScope<'t>) -> R,
+    {
+        let frame = Frame::enter();
+        f(TermScope {
+            depth: frame.depth,
+            _scope: PhantomData,
+        })
+    }
+}
+
+impl<'s> TermScope<'s> {
+    /// Run `f` in a new scope like [TermScope::run], and copy the term it returns into this scope.
+    ///
+    /// ```ignore
+    /// let point = scope.run_term(|inner| {
+    ///     let x = expensive(inner)?;
+    ///     Compound::new("point").arg(x).arg(0).build(inner)
+    /// })?;
+    /// ```
+    pub fn run_term<F>(self, f: F) -> Result<TermRef<'s>, Error>
+    where
+        F: for<'t> FnOnce(TermScope<'t>) -> Result<TermRef<'t>, Error>,
+    {
+        let mut kept = TermRef::new(self);
+        TermScope::run(|scope| kept.put_term(&f(scope)?))?;
+        Ok(kept)
+    }
+
+    /// Keep the term refs created so far until this scope ends, even if scopes nested in it end first.
+    pub(crate) fn pin(self) {
+        pin(self.depth);
+    }
+
+    /// Wrap a term ref that SICStus just created, e.g. with `SP_new_term_ref` or `SP_cons_functor`.
+    pub(crate) fn adopt(self, term_ref: SP_term_ref) -> TermRef<'s> {
+        self.pin();
+        // The term ref is newer than every scope nested in this one, and pinned above.
+        unsafe { TermRef::from_raw(self, term_ref) }
+    }
+
+    pub(crate) fn depth(self) -> usize {
+        self.depth
+    }
+}
+
+/// Keep the term refs created so far alive in all scopes nested deeper than `depth`.
+///
+/// Those scopes then only reclaim the term refs created after this call.
+pub(crate) fn pin(depth: usize) {
+    let mut marks = MARKS.lock();
+    if depth < marks.len() {
+        let mark = sys::sp_new_term_ref();
+        for nested in &mut marks[depth..] {
+            *nested = mark;
+        }
+    }
+}
+
+/// A running scope, which resets the term refs to its mark when dropped.
+struct Frame {
+    depth: usize,
+}
+
+impl Frame {
+    fn enter() -> Self {
+        let mark = sys::sp_new_term_ref();
+        let mut marks = MARKS.lock();
+        marks.push(mark);
+        Frame { depth: marks.len() }
+    }
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        let mark = {
+            let mut marks = MARKS.lock();
+            debug_assert_eq!(marks.len(), self.depth, "scopes end in reverse order");
+            marks.pop().expect("the scope has a mark")
+        };
+        // No term ref of the scope can be used anymore, since they all borrow it.
+        unsafe { sys::sp_reset_term_refs(mark) };
+    }
+}
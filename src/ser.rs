@@ -0,0 +1,409 @@
+//! Serialize Rust values into Prolog terms with serde.
+//!
+//! The data model is mapped as follows:
+//!
+//! | Rust                              | Prolog                                 |
+//! |-----------------------------------|----------------------------------------|
+//! | `bool`                            | `true` / `false`                       |
+//! | integers, floats                  | integers, floats                       |
+//! | `char`, `String`, `&str`          | atom or code list, see [StringStyle]   |
+//! | `Option<T>`                       | `none` / `some(X)`                     |
+//! | `()`                              | `[]`                                   |
+//! | `Vec<T>`, slices, tuples          | list                                   |
+//! | maps                              | list of `Key-Value` pairs              |
+//! | `struct Point { x, y }`           | `point(X, Y)`                          |
+//! | `struct Unit;`                    | `unit`                                 |
+//! | `enum Shape { Circle(f64) }`      | `circle(R)`                            |
+//!
+//! Struct and variant names are converted to snake case so they are valid atoms without quotes.
+
+use alloc::{string::String, vec::Vec};
+use serde::ser::{self, Serialize};
+
+use crate::{
+    convert::{codes_term, list_term},
+    error::Error,
+    exception::{atom_term, compound_term},
+    util::snake_case,
+    TermRef, TermScope, ToTerm,
+};
+
+/// How Rust strings are represented in Prolog.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringStyle {
+    /// `"foo"` becomes the atom `foo`.
+    #[default]
+    Atom,
+    /// `"foo"` becomes the code list `[102, 111, 111]`.
+    Codes,
+}
+
+/// Configuration of the term serializer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    pub strings: StringStyle,
+}
+
+/// Serialize a value into a new Prolog term.
+pub fn to_term<'s, T: Serialize + ?Sized>(
+    scope: TermScope<'s>,
+    value: &T,
+) -> Result<TermRef<'s>, Error> {
+    to_term_with_config(scope, value, Config::default())
+}
+
+/// Serialize a value into a new Prolog term, using the given configuration.
+pub fn to_term_with_config<'s, T: Serialize + ?Sized>(
+    scope: TermScope<'s>,
+    value: &T,
+    config: Config,
+) -> Result<TermRef<'s>, Error> {
+    value.serialize(TermSerializer::new(scope, config))
+}
+
+/// A serde [Serializer](ser::Serializer) producing a [TermRef] in the given scope.
+#[derive(Debug, Clone, Copy)]
+pub struct TermSerializer<'s> {
+    config: Config,
+    scope: TermScope<'s>,
+}
+
+impl<'s> TermSerializer<'s> {
+    pub fn new(scope: TermScope<'s>, config: Config) -> Self {
+        TermSerializer { config, scope }
+    }
+
+    fn compound(self, name: Option<&str>, len: Option<usize>) -> SerializeCompound<'s> {
+        SerializeCompound {
+            config: self.config,
+            scope: self.scope,
+            name: name.map(snake_case),
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        }
+    }
+}
+
+impl<'s> ser::Serializer for TermSerializer<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeCompound<'s>;
+    type SerializeTuple = SerializeCompound<'s>;
+    type SerializeTupleStruct = SerializeCompound<'s>;
+    type SerializeTupleVariant = SerializeCompound<'s>;
+    type SerializeMap = SerializeMap<'s>;
+    type SerializeStruct = SerializeCompound<'s>;
+    type SerializeStructVariant = SerializeCompound<'s>;
+
+    fn serialize_bool(self, v: bool) -> Result<TermRef<'s>, Error> {
+        atom_term(self.scope, if v { "true" } else { "false" })
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<TermRef<'s>, Error> {
+        Ok(TermRef::new_integer(self.scope, v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<TermRef<'s>, Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    /// Values above `i64::MAX` become bignums, like with [ToTerm].
+    fn serialize_u64(self, v: u64) -> Result<TermRef<'s>, Error> {
+        v.to_term(self.scope)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<TermRef<'s>, Error> {
+        v.to_term(self.scope)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<TermRef<'s>, Error> {
+        v.to_term(self.scope)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<TermRef<'s>, Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<TermRef<'s>, Error> {
+        Ok(TermRef::new_float(self.scope, v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<TermRef<'s>, Error> {
+        let mut buf = [0; 4];
+        self.serialize_str(v.encode_utf8(&mut buf))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<TermRef<'s>, Error> {
+        match self.config.strings {
+            StringStyle::Atom => atom_term(self.scope, v),
+            StringStyle::Codes => codes_term(self.scope, v),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<TermRef<'s>, Error> {
+        list_term(
+            self.scope,
+            v.iter()
+                .map(|b| TermRef::new_integer(self.scope, *b as i64))
+                .collect(),
+        )
+    }
+
+    fn serialize_none(self) -> Result<TermRef<'s>, Error> {
+        atom_term(self.scope, "none")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<TermRef<'s>, Error> {
+        compound_term(self.scope, "some", &[value.serialize(self)?])
+    }
+
+    fn serialize_unit(self) -> Result<TermRef<'s>, Error> {
+        atom_term(self.scope, "[]")
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<TermRef<'s>, Error> {
+        atom_term(self.scope, &snake_case(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<TermRef<'s>, Error> {
+        atom_term(self.scope, &snake_case(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<TermRef<'s>, Error> {
+        compound_term(self.scope, &snake_case(name), &[value.serialize(self)?])
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<TermRef<'s>, Error> {
+        compound_term(self.scope, &snake_case(variant), &[value.serialize(self)?])
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(None, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(None, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(Some(name), Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(Some(variant), Some(len)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap<'s>, Error> {
+        Ok(SerializeMap {
+            config: self.config,
+            scope: self.scope,
+            pairs: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(Some(name), Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeCompound<'s>, Error> {
+        Ok(self.compound(Some(variant), Some(len)))
+    }
+}
+
+/// Collects the elements of a list, or the arguments of a compound term if it has a name.
+pub struct SerializeCompound<'s> {
+    config: Config,
+    scope: TermScope<'s>,
+    name: Option<String>,
+    items: Vec<TermRef<'s>>,
+}
+
+impl<'s> SerializeCompound<'s> {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let item = value.serialize(TermSerializer::new(self.scope, self.config))?;
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<TermRef<'s>, Error> {
+        match self.name {
+            None => list_term(self.scope, self.items),
+            Some(name) if self.items.is_empty() => atom_term(self.scope, &name),
+            Some(name) => compound_term(self.scope, &name, &self.items),
+        }
+    }
+}
+
+impl<'s> ser::SerializeSeq for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+impl<'s> ser::SerializeTuple for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+impl<'s> ser::SerializeTupleStruct for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+impl<'s> ser::SerializeTupleVariant for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+impl<'s> ser::SerializeStruct for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+impl<'s> ser::SerializeStructVariant for SerializeCompound<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        self.finish()
+    }
+}
+
+/// Collects the entries of a map as a list of `Key-Value` pairs.
+pub struct SerializeMap<'s> {
+    config: Config,
+    scope: TermScope<'s>,
+    pairs: Vec<TermRef<'s>>,
+    key: Option<TermRef<'s>>,
+}
+
+impl<'s> ser::SerializeMap for SerializeMap<'s> {
+    type Ok = TermRef<'s>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(TermSerializer::new(self.scope, self.config))?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().ok_or_else(|| {
+            Error::Conversion(String::from("map value serialized before its key"))
+        })?;
+        let value = value.serialize(TermSerializer::new(self.scope, self.config))?;
+        self.pairs
+            .push(compound_term(self.scope, "-", &[key, value])?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<TermRef<'s>, Error> {
+        list_term(self.scope, self.pairs)
+    }
+}
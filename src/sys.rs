@@ -24,55 +24,16 @@ pub use sicstus_sys::{
 
 pub use sicstus_sys::SP_printf;
 
-mod error {
-
-    use alloc::string::String;
-
-    use crate::sys::SP_term_ref;
-
-    #[derive(Debug)]
-    pub enum PrologError {
-        TermConversionError(String),
-        NoTermVariantMatch,
-        AtomNotFound(String),
-        CloseQueryError(String),
-        UnexpectedReturnCode(i32),
-        NoExceptionTerm(SP_term_ref),
-        UncussefulUnificationError(i32, i32),
-        ConsFunctorError,
-        QueryOpenUnsuccessful,
-        PredicateNotFound,
-        NextSolutionError(String),
-        NoMoreSolutions,
-        CutQueryError(String),
-        CutQueryFailure,
-        DefineCPredicateError,
-        TypeCheckError,
-        AtomRegistrationError(u64),
-        AtomUnregistrationError(u64),
-        TypeError,
-    }
-
-    // region:    --- Error Boilerplate
-    impl core::fmt::Display for PrologError {
-        fn fmt(
-            &self,
-            fmt: &mut core::fmt::Formatter,
-        ) -> core::result::Result<(), core::fmt::Error> {
-            write!(fmt, "{self:?}")
-        }
-    }
-}
-
 use alloc::format;
-pub use error::PrologError;
 use sicstus_sys::*;
 
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use alloc::vec::Vec;
 
-use crate::error::SicstusRsError;
-use crate::util::string_from_ref;
+use crate::error::{ApiError, Error};
+use crate::exception::PrologException;
+use crate::util::{string_from_ref, to_cstring};
+use crate::view::TermType;
 
 use core::cmp::Ordering;
 use core::ffi::{c_char, c_int, CStr};
@@ -80,22 +41,61 @@ use core::ffi::{c_uchar, c_void};
 
 use super::*;
 
-/// TODO ALERT! Not working yet at all!
-pub fn sp_initialze(argc: usize, argv: Vec<String>) -> Result<(), SicstusRsError> {
-    unsafe {
-        let ret_val = SP_initialize(
-            argc as c_int,
-            argv.as_ptr() as *mut *mut c_char,
-            core::ptr::null_mut(),
-        );
-        if ret_val == SP_FAILURE as i32 {
-            Err(SicstusRsError::InitializationError(sp_err_message()))
-        } else {
-            Ok(())
-        }
+/// Start the SICStus runtime when embedding it in a program, see [Runtime](crate::Runtime).
+///
+/// `argv` holds the command line arguments followed by a null pointer, like the `argv` of a C `main`.
+/// `properties` are system properties as key value pairs, e.g. `SP_ULIMIT_DATA_SEGMENT_SIZE`.
+pub fn sp_initialize(
+    argv: &mut [*mut c_char],
+    properties: &[(CString, CString)],
+) -> Result<(), Error> {
+    let mut options: Vec<SP_option> = properties
+        .iter()
+        .map(|(key, value)| {
+            let mut option: SP_option = unsafe { core::mem::zeroed() };
+            option.type_ = SP_option_type_system_property as _;
+            option.u.prop.key = key.as_ptr();
+            option.u.prop.value = value.as_ptr();
+            option
+        })
+        .collect();
+    let options = SP_options {
+        noptions: options.len() as _,
+        options: options.as_mut_ptr(),
+    };
+    let code = unsafe { SP_initialize(argv.len() as c_int - 1, argv.as_mut_ptr(), &options) };
+    if code == SP_SUCCESS as c_int {
+        Ok(())
+    } else {
+        // The error functions need an initialized runtime.
+        Err(Error::Initialization(ApiError {
+            function: "SP_initialize",
+            code,
+            message: String::from("the runtime could not be started"),
+        }))
+    }
+}
+
+/// Set the `argv` Prolog flag from `argv`, which is terminated by a null pointer like the `argv` of a C `main`.
+pub fn sp_set_argv(argv: &mut [*mut c_char]) -> Result<(), Error> {
+    let code = unsafe { SP_set_argv(argv.len() as c_int - 1, argv.as_mut_ptr(), 0) };
+    if code == SP_SUCCESS as c_int {
+        Ok(())
+    } else {
+        Err(Error::api("SP_set_argv", code, unsafe { sp_err_message() }))
     }
 }
 
+/// Stop the runtime started by [sp_initialize].
+pub fn sp_deinitialize() {
+    unsafe { SP_deinitialize() }
+}
+
+/// Whether the runtime is running, either started by [sp_initialize] or because Prolog loaded this code.
+pub fn sp_is_initialized() -> bool {
+    SP_is_initialized()
+}
+
 /// Get the error message from Prolog when a SP_ERROR is returned by a function.
 ///
 /// # Returns
@@ -111,17 +111,36 @@ pub unsafe fn sp_err_message() -> String {
 /// Retracts the current pending exception term, if it exits.
 ///
 /// # Returns
-/// Result of either the successfully extracted exception term, or an error if no exception term exists.
+/// Result of either the extracted exception term, or None if there is no pending exception.
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Exception%20Handling%20in%20C>
-pub fn sp_exception_term() -> Result<SP_term_ref, PrologError> {
+pub fn sp_exception_term() -> Result<Option<SP_term_ref>, Error> {
     let term = sp_new_term_ref();
     let return_value = unsafe { SP_exception_term(term) };
     if return_value == 1 {
-        Ok(term)
+        Ok(Some(term))
     } else if return_value == 0 {
-        Err(PrologError::NoExceptionTerm(term))
+        Ok(None)
     } else {
-        Err(PrologError::UnexpectedReturnCode(return_value))
+        Err(Error::api(
+            "SP_exception_term",
+            return_value,
+            String::from("Unexpected return code."),
+        ))
+    }
+}
+
+/// Build the error for a SICStus API function that returned SP_ERROR.
+/// This is the pending exception if there is one, and the SICStus error message otherwise.
+///
+/// # Safety
+/// This should only be called when a SP_ERROR is actually returned, see [sp_err_message].
+unsafe fn sp_error(function: &'static str) -> Error {
+    match PrologException::take_pending() {
+        Some(exception) => Error::Exception {
+            function,
+            exception,
+        },
+        None => Error::api(function, SP_ERROR, sp_err_message()),
     }
 }
 
@@ -147,28 +166,32 @@ pub fn sp_raise_exception(term: SP_term_ref) {
     unsafe { SP_raise_exception(term) }
 }
 
-/// Reads the given string as prolog Code and assigns it
-/// Variables are
-pub fn sp_read_from_string(
-    string: &str,
-    values: Vec<SP_term_ref>,
-) -> Result<SP_term_ref, PrologError> {
-    let mut values = values;
-    values.push(0); // Null terminate the array for safety. If it is already terminated it should't hurt.
+/// Read a term from its textual representation, which must be terminated by a full stop.
+///
+/// # Arguments
+/// * string - The Prolog text, e.g. `"foo(X, Y, bar)."`.
+/// * values - The values of the variables in the text, in the order of their first occurrence.
+///   Every anonymous variable `_` is a variable of its own. Variables without a value stay unbound.
+///
+/// # Returns
+/// The read term. Syntax errors are returned as [Error::Exception].
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Creating%20Prolog%20Terms>
+pub fn sp_read_from_string(string: &str, values: &[SP_term_ref]) -> Result<SP_term_ref, Error> {
+    let cstring = to_cstring(string)?;
+    // The values are terminated by 0.
+    let mut values = values.to_vec();
+    values.push(0);
     let term = sp_new_term_ref();
-    let ret_val = unsafe {
-        SP_read_from_string(
-            term,
-            string.as_ptr() as *const c_char,
-            values.as_ptr() as *mut SP_term_ref,
-        )
-    };
-    if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-                "Problem reading the prolog code {} with values {:?} into a term with SP_read_from_string.",
-                string,
-                values
-            )))
+    let ret_val = unsafe { SP_read_from_string(term, cstring.as_ptr(), values.as_mut_ptr()) };
+    if ret_val == SP_ERROR {
+        // We got a SP_ERROR, so the safety condition is met and we can call sp_error.
+        unsafe { Err(sp_error("SP_read_from_string")) }
+    } else if ret_val == 0 {
+        Err(Error::api(
+            "SP_read_from_string",
+            ret_val,
+            format!("Could not read {} into a term.", string),
+        ))
     } else {
         Ok(term)
     }
@@ -184,11 +207,15 @@ pub fn sp_read_from_string(
 /// # Description
 /// Prolog has a unique integer ID for each atom. This represenation is needed for some of the C API functions.
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Creating%20and%20Manipulating%20SP_term_refs>
-pub fn sp_atom_from_string(atom_name: &str) -> Result<SP_atom, PrologError> {
-    let atom_cstring: CString = CString::new(atom_name).unwrap();
-    let atom_id: SP_atom = unsafe { SP_atom_from_string(atom_cstring.as_ptr() as *const c_char) };
+pub fn sp_atom_from_string(atom_name: &str) -> Result<SP_atom, Error> {
+    let atom_cstring: CString = to_cstring(atom_name)?;
+    let atom_id: SP_atom = unsafe { SP_atom_from_string(atom_cstring.as_ptr()) };
     if atom_id == 0 {
-        Err(PrologError::AtomNotFound(atom_name.to_string()))
+        Err(Error::api(
+            "SP_atom_from_string",
+            0,
+            format!("Could not find or create the atom {}.", atom_name),
+        ))
     } else {
         Ok(atom_id)
     }
@@ -227,15 +254,19 @@ pub fn sp_atom_length(atom: SP_atom) -> usize {
 /// backtrack into the query, throwing away any current solution, like the goal !, fail. The
 /// given argument does not have to be the innermost open query; any open queries in its scope will also be closed.
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Finding%20Multiple%20Solutions%20of%20a%20Call>
-pub fn sp_close_query(query: SP_qid) -> Result<(), PrologError> {
+pub fn sp_close_query(query: SP_qid) -> Result<(), Error> {
     let res = unsafe { SP_close_query(query) };
     if res == SP_ERROR {
-        // We got a SP_ERROR, so the safety condition is met and we can call sp_error_message.
-        unsafe { Err(PrologError::CloseQueryError(sp_err_message())) }
+        // We got a SP_ERROR, so the safety condition is met and we can call sp_error.
+        unsafe { Err(sp_error("SP_close_query")) }
     } else if res == SP_SUCCESS as c_int {
         Ok(())
     } else {
-        Err(PrologError::UnexpectedReturnCode(res))
+        Err(Error::api(
+            "SP_close_query",
+            res,
+            String::from("Unexpected return code."),
+        ))
     }
 }
 
@@ -255,26 +286,35 @@ pub fn sp_close_query(query: SP_qid) -> Result<(), PrologError> {
 /// Ok(SP_term_ref) of the assigned new term if the conversion was successful, and Err otherwise.
 ///
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Creating%20Prolog%20Terms>
-pub fn sp_cons_functor(name: SP_atom, args: &[SP_term_ref]) -> Result<SP_term_ref, PrologError> {
+pub fn sp_cons_functor(name: SP_atom, args: &[SP_term_ref]) -> Result<SP_term_ref, Error> {
     let term = sp_new_term_ref();
+    // We call the array version of the C API because rust does not support variadic functions.
+    sp_cons_functor_array(term, name, args)?;
+    Ok(term)
+}
+
+/// Assigns to *term* a compound term with functor *name* whose arguments are the values of *args*.
+/// The arity is the length of *args*. See [sp_cons_functor].
+pub fn sp_cons_functor_array(
+    term: SP_term_ref,
+    name: SP_atom,
+    args: &[SP_term_ref],
+) -> Result<(), Error> {
+    // SICStus only reads from the argument array.
     let arg_p = args.as_ptr() as *mut SP_term_ref;
     let arity = args.len() as c_int;
-    // We call the array version of the C API because rust does not support variadic functions.
     let ret_value = unsafe { SP_cons_functor_array(term, name, arity, arg_p) };
     if ret_value == 0 {
-        Err(PrologError::ConsFunctorError)
+        Err(Error::api(
+            "SP_cons_functor_array",
+            ret_value,
+            format!("Could not create a compound term with {} arguments.", arity),
+        ))
     } else {
-        Ok(term)
+        Ok(())
     }
 }
 
-/// Not implemented, use [sp_cons_functor] instead.
-pub fn sp_cons_functor_array() -> c_int {
-    unimplemented!(
-        "Use sp_cons_functor instead, since it already uses the array version of the C API."
-    )
-}
-
 /// Assigns to term a reference to a Prolog list whose head and tail are the values of head and tail.
 ///
 /// # Arguments
@@ -284,17 +324,17 @@ pub fn sp_cons_functor_array() -> c_int {
 /// # Returns
 /// Ok(term_ref) of the assigned new term if the conversion was successful, and Err otherwise.
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Creating%20Prolog%20Terms>
-pub fn sp_cons_list(
-    term: SP_term_ref,
-    head: SP_term_ref,
-    tail: SP_term_ref,
-) -> Result<(), PrologError> {
+pub fn sp_cons_list(term: SP_term_ref, head: SP_term_ref, tail: SP_term_ref) -> Result<(), Error> {
     let ret_val = unsafe { SP_cons_list(term, head, tail) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Could not convert head {:?} and tail {:?} to a list.",
-            head, tail
-        )))
+        Err(Error::api(
+            "SP_cons_list",
+            ret_val,
+            format!(
+                "Could not convert head {:?} and tail {:?} to a list.",
+                head, tail
+            ),
+        ))
     } else {
         Ok(())
     }
@@ -314,17 +354,25 @@ pub fn sp_cons_list(
 /// !. The current solution is retained in the arguments until backtracking into any enclosing
 /// query. The given argument does not have to be the innermost open query; any open queries
 /// in its scope will also be cut.
-pub fn sp_cut_query(query: SP_qid) -> Result<(), PrologError> {
+pub fn sp_cut_query(query: SP_qid) -> Result<(), Error> {
     let re_val = unsafe { SP_cut_query(query) };
     if re_val == SP_ERROR {
-        // We got a SP_ERROR, so the safety condition is met and we can call sp_error_message.
-        unsafe { Err(PrologError::CutQueryError(sp_err_message())) }
+        // We got a SP_ERROR, so the safety condition is met and we can call sp_error.
+        unsafe { Err(sp_error("SP_cut_query")) }
     } else if re_val == SP_FAILURE as c_int {
-        Err(PrologError::CutQueryFailure)
+        Err(Error::api(
+            "SP_cut_query",
+            re_val,
+            String::from("The query could not be cut."),
+        ))
     } else if re_val == SP_SUCCESS as c_int {
         Ok(())
     } else {
-        Err(PrologError::UnexpectedReturnCode(re_val))
+        Err(Error::api(
+            "SP_cut_query",
+            re_val,
+            String::from("Unexpected return code."),
+        ))
     }
 }
 
@@ -338,7 +386,7 @@ pub fn sp_cut_query(query: SP_qid) -> Result<(), PrologError> {
 /// * stash - See below.
 ///
 /// # Return Value
-/// Nonzero on success, and 0 otherwise.
+/// Ok(()) on success, and Err otherwise.
 ///
 /// # Description
 ///  The Prolog predicate module:name/arity will be defined (the module module must already exist).
@@ -346,45 +394,62 @@ pub fn sp_cut_query(query: SP_qid) -> Result<(), PrologError> {
 ///  The C function should return SP_SUCCESS for success and SP_FAILURE for failure. The C
 ///  function may also call SP_fail() or SP_raise_exception() in which case the return value will be ignored.
 pub fn sp_define_c_predicate(
-    name: *const c_char,
-    arity: c_int,
-    module: *const c_char,
+    name: &str,
+    arity: usize,
+    module: &str,
     proc: SP_CPredFun,
     stash: *mut c_void,
-) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_define_c_predicate(name, arity, module, proc, stash) };
+) -> Result<(), Error> {
+    let name_c = to_cstring(name)?;
+    let module_c = to_cstring(module)?;
+    let ret_val = unsafe {
+        SP_define_c_predicate(
+            name_c.as_ptr(),
+            arity as c_int,
+            module_c.as_ptr(),
+            proc,
+            stash,
+        )
+    };
     if ret_val == 0 {
-        Err(PrologError::DefineCPredicateError)
+        Err(Error::api(
+            "SP_define_c_predicate",
+            ret_val,
+            format!(
+                "Could not define the predicate {}:{}/{}.",
+                module, name, arity
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Returns a pointer to the
-pub fn sp_get_address(term: SP_term_ref) -> Result<*mut c_void, PrologError> {
-    let p = core::ptr::null_mut();
-    let ret_val = unsafe { SP_get_address(term, p) };
+pub fn sp_get_address(term: SP_term_ref) -> Result<*mut c_void, Error> {
+    let mut p: *mut c_void = core::ptr::null_mut();
+    let ret_val = unsafe { SP_get_address(term, &mut p) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Could not convert term {:?} to a pointer.",
-            term
-        )))
+        Err(Error::api(
+            "SP_get_address",
+            ret_val,
+            format!("Could not convert term {:?} to a pointer.", term),
+        ))
     } else {
-        unsafe {
-            return Ok(*p);
-        }
+        Ok(p)
     }
 }
 
 /// Returns a SP_term_ref to the i'th argument of a compound *term*.
-pub fn sp_get_arg(i: usize, term: SP_term_ref) -> Result<SP_term_ref, PrologError> {
+pub fn sp_get_arg(i: usize, term: SP_term_ref) -> Result<SP_term_ref, Error> {
     let arg = sp_new_term_ref();
     let ret_val = unsafe { SP_get_arg(i as c_int, term, arg) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed getting the {}th arg in term {:?}.",
-            i, term
-        )))
+        Err(Error::api(
+            "SP_get_arg",
+            ret_val,
+            format!("Failed getting the {}th arg in term {:?}.", i, term),
+        ))
     } else {
         Ok(arg)
     }
@@ -397,15 +462,16 @@ pub fn sp_get_arg(i: usize, term: SP_term_ref) -> Result<SP_term_ref, PrologErro
 ///
 /// # Returns
 /// Ok(SP_atom) of the canonical representation of the atom if the conversion was successful, and Err otherwise.
-pub fn sp_get_atom(term: SP_term_ref) -> Result<SP_atom, PrologError> {
+pub fn sp_get_atom(term: SP_term_ref) -> Result<SP_atom, Error> {
     let mut atom = SP_atom::default();
     let atom_ptr = &mut atom as *mut SP_atom;
-    let ret_val = unsafe { SP_get_atom(term, atom_ptr) as u32 };
+    let ret_val = unsafe { SP_get_atom(term, atom_ptr) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Could not convert term {:?} to an atom.",
-            term
-        )))
+        Err(Error::api(
+            "SP_get_atom",
+            ret_val,
+            format!("Could not convert term {:?} to an atom.", term),
+        ))
     } else {
         Ok(atom)
     }
@@ -419,17 +485,18 @@ pub fn sp_get_atom(term: SP_term_ref) -> Result<SP_atom, PrologError> {
 /// * `term_ref` - The term reference to convert.
 /// # Returns a Result
 /// * `Ok(i)` - The integer value of the term reference wrapped in an [Ok] variant.
-/// * `Err(PrologError::TermConversionError)` - If the term reference could not be converted.
-pub fn sp_get_integer(term_ref: SP_term_ref) -> Result<i64, PrologError> {
+/// * `Err(Error::Api)` - If the term reference could not be converted.
+pub fn sp_get_integer(term_ref: SP_term_ref) -> Result<i64, Error> {
     unsafe {
         let mut i: SP_integer = 0;
         let p: *mut SP_integer = &mut i;
         let ret_val: c_int = SP_get_integer(term_ref, p);
         if ret_val == 0 {
-            Err(PrologError::TermConversionError(format!(
-                "Could not retrieve term {:?} as an integer.",
-                term_ref
-            )))
+            Err(Error::api(
+                "SP_get_integer",
+                ret_val,
+                format!("Could not retrieve term {:?} as an integer.", term_ref),
+            ))
         } else {
             Ok(i)
         }
@@ -438,16 +505,17 @@ pub fn sp_get_integer(term_ref: SP_term_ref) -> Result<i64, PrologError> {
 
 /// Save wrapper around the unsafe [SP_get_float] function from Prolog.
 /// If the term is an Integer that does not fit in a double, then the call will fail.
-pub fn sp_get_float(term: SP_term_ref) -> Result<f64, PrologError> {
+pub fn sp_get_float(term: SP_term_ref) -> Result<f64, Error> {
     unsafe {
         let mut f: f64 = 0.0;
         let p: *mut f64 = &mut f;
         let ret_val: c_int = SP_get_float(term, p);
         if ret_val == 0 {
-            Err(PrologError::TermConversionError(format!(
-                "Could not retrieve term {:?} as a float.",
-                term
-            )))
+            Err(Error::api(
+                "SP_get_float",
+                ret_val,
+                format!("Could not retrieve term {:?} as a float.", term),
+            ))
         } else {
             Ok(f)
         }
@@ -459,30 +527,51 @@ pub fn sp_get_float(term: SP_term_ref) -> Result<f64, PrologError> {
 /// * `term` - The term reference to convert.
 /// # Returns a Result of
 /// * `Ok((atom, arity))` - The atom and arity of the term reference wrapped in an [Ok] variant.
-/// * `Err(PrologError::TermConversionError)` - If the term reference could not be converted.
-pub fn sp_get_functor(term: SP_term_ref) -> Result<(SP_atom, usize), PrologError> {
+/// * `Err(Error::Api)` - If the term reference could not be converted.
+pub fn sp_get_functor(term: SP_term_ref) -> Result<(SP_atom, usize), Error> {
     let mut atom = SP_atom::default();
     let atom_ptr = &mut atom as *mut SP_atom;
     let mut arity: c_int = 0;
     let arity_ptr = &mut arity as *mut c_int;
     let ret_val = unsafe { SP_get_functor(term, atom_ptr, arity_ptr) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Could not retrieve term {:?} as a functor.",
-            term
-        )))
+        Err(Error::api(
+            "SP_get_functor",
+            ret_val,
+            format!("Could not retrieve term {:?} as a functor.", term),
+        ))
     } else {
         Ok((atom, arity as usize))
     }
 }
 
-pub fn sp_get_integer_bytes(
-    term: SP_term_ref,
-    buf: *mut c_void,
-    pbuf_size: *mut usize,
-    native: c_int,
-) -> c_int {
-    unsafe { SP_get_integer_bytes(term, buf, pbuf_size, native) }
+/// Save wrapper around the unsafe [SP_get_integer_bytes] function from Prolog.
+/// Unlike [sp_get_integer] this works for integers of any size.
+/// # Returns a Result of
+/// * `Ok(bytes)` - The two's complement of the integer, least significant byte first, in as few bytes as SICStus needs.
+/// * `Err(Error::Api)` - If the term is not an integer.
+pub fn sp_get_integer_bytes(term: SP_term_ref) -> Result<Vec<u8>, Error> {
+    let error = |ret_val| {
+        Error::api(
+            "SP_get_integer_bytes",
+            ret_val,
+            format!("Could not retrieve term {:?} as integer bytes.", term),
+        )
+    };
+    // Called with an empty buffer, SICStus only reports the size it needs.
+    let mut size: usize = 0;
+    unsafe { SP_get_integer_bytes(term, core::ptr::null_mut(), &mut size, 0) };
+    if size == 0 {
+        return Err(error(0));
+    }
+    let mut buf = alloc::vec![0u8; size];
+    let ret_val =
+        unsafe { SP_get_integer_bytes(term, buf.as_mut_ptr() as *mut c_void, &mut size, 0) };
+    if ret_val == 0 {
+        return Err(error(ret_val));
+    }
+    buf.truncate(size);
+    Ok(buf)
 }
 
 pub fn sp_get_list(list: SP_term_ref) -> Option<(SP_term_ref, SP_term_ref)> {
@@ -496,21 +585,18 @@ pub fn sp_get_list(list: SP_term_ref) -> Option<(SP_term_ref, SP_term_ref)> {
     }
 }
 
-pub fn sp_get_list_codes(term: SP_term_ref) -> Result<String, PrologError> {
-    let s = core::ptr::null_mut();
-    let ret_val = unsafe { SP_get_list_codes(term, s) };
-    if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Could not convert term {:?} to a list of codes.",
-            term
-        )))
+pub fn sp_get_list_codes(term: SP_term_ref) -> Result<String, Error> {
+    let mut s: *const c_char = core::ptr::null();
+    let ret_val = unsafe { SP_get_list_codes(term, &mut s) };
+    if ret_val == 0 || s.is_null() {
+        Err(Error::api(
+            "SP_get_list_codes",
+            ret_val,
+            format!("Could not convert term {:?} to a list of codes.", term),
+        ))
     } else {
-        unsafe {
-            Ok(CStr::from_ptr(s as *const c_char)
-                .to_str()
-                .unwrap()
-                .to_string())
-        }
+        // SICStus returned a null terminated UTF-8 string.
+        unsafe { Ok(string_from_ref(s)) }
     }
 }
 
@@ -542,16 +628,17 @@ pub fn sp_get_number_codes(term: SP_term_ref, s: *mut *const c_char) -> c_int {
 /// * `term_ref` - The term reference to convert.
 /// # Returns a Result of
 /// * `Ok(s)` - The String value of the term reference wrapped in an [Ok] variant.
-/// * `Err(PrologError::TermConversionError)` - If the term reference could not be converted.
-pub fn sp_get_string(term_ref: SP_term_ref) -> Result<String, PrologError> {
+/// * `Err(Error::Api)` - If the term reference could not be converted.
+pub fn sp_get_string(term_ref: SP_term_ref) -> Result<String, Error> {
     unsafe {
         let mut s: *const c_char = core::ptr::null_mut();
         let ret_val: c_int = SP_get_string(term_ref, &mut s as *mut *const c_char);
         if ret_val == 0 || s.is_null() {
-            Err(PrologError::TermConversionError(format!(
-                "Could not retrieve term {:?} as a string.",
-                term_ref
-            )))
+            Err(Error::api(
+                "SP_get_string",
+                ret_val,
+                format!("Could not retrieve term {:?} as a string.", term_ref),
+            ))
         } else {
             Ok(string_from_ref(s))
         }
@@ -584,106 +671,158 @@ pub fn sp_get_string(term_ref: SP_term_ref) -> Result<String, PrologError> {
 /// * query - The query, created by [SP_open_query].
 /// # Return Value
 /// Ok(()) for success, and appropriate error otherwise.
+/// If the query raised an exception, it is returned as [Error::Exception].
 /// # Description
 /// This will cause the Prolog engine to backtrack over any current solution of an open query
 /// and look for a new one. The given argument must be the innermost query that is still open,
 /// i.e. it must not have been terminated explicitly by SP_close_query() or SP_cut_query().
 /// Only when the return value is SP_SUCCESS are the values in the query arguments valid, and
 /// will remain so until backtracking into this query or an enclosing one.
-pub fn sp_next_solution(query: SP_qid) -> Result<(), PrologError> {
+pub fn sp_next_solution(query: SP_qid) -> Result<(), Error> {
     let ret_val: c_int = unsafe { SP_next_solution(query) };
     if ret_val == SP_ERROR {
-        // We got a SP_ERROR, so the safety condition is met and we can call sp_error_message.
-        unsafe { Err(PrologError::NextSolutionError(sp_err_message())) }
+        // We got a SP_ERROR, so the safety condition is met and we can call sp_error.
+        unsafe { Err(sp_error("SP_next_solution")) }
     } else if ret_val == SP_FAILURE as c_int {
-        Err(PrologError::NoMoreSolutions)
+        Err(Error::NoMoreSolutions)
     } else if ret_val == SP_SUCCESS as c_int {
         Ok(())
     } else {
-        Err(PrologError::UnexpectedReturnCode(ret_val))
+        Err(Error::api(
+            "SP_next_solution",
+            ret_val,
+            String::from("Unexpected return code."),
+        ))
     }
 }
 
-#[macro_export]
-macro_rules! open_query {
-    ($pred:expr,$($arg:expr),*) => {
-        unsafe {
-            let rewopar_valkjdasöfl = SP_open_query($pred, $($arg),*);
-            if rewopar_valkjdasöfl == 0 {
-                Err(PrologError::QueryOpenUnsuccessful)
-            } else {
-                Ok(rewopar_valkjdasöfl)
-            }
-        }
-    };
+/// Sets up a query for use by [sp_next_solution], [sp_close_query] and [sp_cut_query].
+///
+/// # Arguments
+/// * goal - The goal to run, usually a module qualified term `Module:Goal`.
+///
+/// # Returns
+/// Result of SP_qid if the query was opened successfully, and Err otherwise.
+///
+/// # Description
+/// SP_open_query is variadic in C, and Rust cannot pass it a number of arguments that is only known at runtime.
+/// Instead the whole goal is passed to call/1, which works for predicates of any arity.
+/// Variables in the goal are shared with the term refs it was built from, so the bindings of a
+/// solution can be read through them.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Finding%20Multiple%20Solutions%20of%20a%20Call>
+pub fn sp_open_query(goal: SP_term_ref) -> Result<SP_qid, Error> {
+    let call = sp_predicate("call", 1, Some("prolog"))?;
+    let query: SP_qid = SP_open_query!(call, goal);
+    if query.is_null() {
+        Err(Error::api(
+            "SP_open_query",
+            0,
+            String::from("Could not open the query."),
+        ))
+    } else {
+        Ok(query)
+    }
 }
 
-/// Sets up a query for use by [SP_next_solution]. [SP_close_query] and [SP_cut_query]. Only supports arg arrays up to 15 args.
+/// Calls a goal once, like once/1, keeping the bindings of its first solution.
+///
 /// # Arguments
-/// * `predicate` - The predicate to query.
-/// * `arg1` - The first argument of the predicate.
+/// * goal - The goal to run, usually a module qualified term `Module:Goal`.
+///
 /// # Returns
-/// Result of SP_qid if the query was successful, and Err otherwise.
+/// Ok(true) if the goal succeeded, Ok(false) if it failed, and Err if it raised an exception.
+///
 /// # Description
-/// This function still contains a very ugly hack because Rust does not support this type of syntax.
-/// If you want larger queries, call into the C API directly.
-// pub fn sp_open_query(
-//     predicate: SP_pred_ref,
-//     args: Vec<SP_term_ref>,
-// ) -> Result<SP_qid, PrologError> {
-//     // TODO Rewrite with proc macro
-//     unsafe {
-//         let result = match args.len() {
-//             0 => panic!("Not enough arguments!"),
-//             1 => SP_open_query!(predicate, args[0]),
-//             2 => SP_open_query!(predicate, args[0], args[1]),
-//             3 => SP_open_query!(predicate, args[0], args[1], args[2]),
-//             4 => SP_open_query!(predicate, args[0], args[1], args[2], args[3]),
-//             5 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4]),
-//             6 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5]),
-//             7 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6]),
-//             8 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7]),
-//             9 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8]),
-//             10 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9]),
-//             11 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9], args[10]),
-//             12 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9], args[10], args[11]),
-//             13 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9], args[10], args[11], args[12]),
-//             14 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9], args[10], args[11], args[12], args[13]),
-//             15 => SP_open_query!(predicate, args[0], args[1], args[2], args[3], args[4], args[5], args[6], args[7], args[8], args[9], args[10], args[11], args[12], args[13], args[14]),
-//             _ => panic!("Queries of more than 15 args are not supported by the wrapper API. For large Queries call the C API directly"),
-//         };
-//         if result == 0 {
-//             Err(PrologError::QueryOpenUnsuccessful)
-//         } else {
-//             Ok(result)
-//         }
-//     }
-// }
+/// Like [sp_open_query] the goal is passed to call/1, because SP_query is variadic in C.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Finding%20One%20Solution%20of%20a%20Call>
+pub fn sp_query(goal: SP_term_ref) -> Result<bool, Error> {
+    let call = sp_predicate("call", 1, Some("prolog"))?;
+    let ret_val: c_int = SP_query!(call, goal);
+    query_result("SP_query", ret_val)
+}
+
+/// Calls a goal once for its side effects, like `\+ \+ once(Goal)`, discarding any bindings.
+///
+/// # Arguments
+/// * goal - The goal to run, usually a module qualified term `Module:Goal`.
+///
+/// # Returns
+/// Ok(true) if the goal succeeded, Ok(false) if it failed, and Err if it raised an exception.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Finding%20One%20Solution%20of%20a%20Call>
+pub fn sp_query_cut_fail(goal: SP_term_ref) -> Result<bool, Error> {
+    let call = sp_predicate("call", 1, Some("prolog"))?;
+    let ret_val: c_int = SP_query_cut_fail!(call, goal);
+    query_result("SP_query_cut_fail", ret_val)
+}
+
+fn query_result(function: &'static str, ret_val: c_int) -> Result<bool, Error> {
+    if ret_val == SP_ERROR {
+        // We got a SP_ERROR, so the safety condition is met and we can call sp_error.
+        unsafe { Err(sp_error(function)) }
+    } else if ret_val == SP_FAILURE as c_int {
+        Ok(false)
+    } else if ret_val == SP_SUCCESS as c_int {
+        Ok(true)
+    } else {
+        Err(Error::api(
+            function,
+            ret_val,
+            String::from("Unexpected return code."),
+        ))
+    }
+}
 
 /// Returns a pointer to the predicate definition.
 ///
 /// # Return Value
 /// The reference if the predicate is found, NULL otherwise with error code PRED_NOT_FOUND.
-pub fn sp_pred(
-    name_atom: SP_atom,
-    arity: u32,
-    module_atom: SP_atom,
-) -> Result<SP_pred_ref, PrologError> {
+pub fn sp_pred(name_atom: SP_atom, arity: u32, module_atom: SP_atom) -> Result<SP_pred_ref, Error> {
     let ret_val = unsafe { SP_pred(name_atom, arity as SP_integer, module_atom) };
     if ret_val.is_null() {
-        Err(PrologError::PredicateNotFound)
+        Err(Error::PredicateNotFound(format!(
+            "{}:{}/{}",
+            sp_string_from_atom(module_atom),
+            sp_string_from_atom(name_atom),
+            arity
+        )))
     } else {
         Ok(ret_val)
     }
 }
 
-pub fn sp_expand_file_name(
-    relpath: *const c_char,
-    cwd: *mut c_char,
-    options: spio_t_bits,
-    pabspath: *mut *mut c_char,
-) -> c_int {
-    unsafe { SP_expand_file_name(relpath, cwd, options, pabspath) }
+/// Make a file name absolute, the way Prolog does when opening a file.
+///
+/// Relative names are taken relative to the working directory of SICStus, and `~` and `$VAR` are expanded.
+///
+/// # Arguments
+/// * relpath - The file name to expand.
+/// * options - `SP_EXPAND_FILE_NAME_OPTION_*` bits, or 0 for the defaults.
+///
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/html/sicstus.html/cpg_002dref_002dSP_005fexpand_005ffile_005fname.html>
+pub fn sp_expand_file_name(relpath: &str, options: spio_t_bits) -> Result<String, Error> {
+    let crelpath = to_cstring(relpath)?;
+    let mut abspath: *mut c_char = core::ptr::null_mut();
+    let ret_val = unsafe {
+        SP_expand_file_name(
+            crelpath.as_ptr(),
+            core::ptr::null_mut(),
+            options,
+            &mut abspath,
+        )
+    };
+    // Errors are negative spio error codes.
+    if ret_val < 0 || abspath.is_null() {
+        return Err(Error::api(
+            "SP_expand_file_name",
+            ret_val,
+            format!("Could not expand the file name {}.", relpath),
+        ));
+    }
+    unsafe {
+        let path = string_from_ref(abspath);
+        SP_free(abspath as *mut c_void);
+        Ok(path)
+    }
 }
 
 // pub fn sp_get_byte(stream: *mut SP_stream) -> spio_t_error_code {
@@ -751,11 +890,40 @@ pub fn sp_fopen(
 //     unsafe { super::sys::user_flush_output(user_data, flush_options) }
 // }
 
-pub fn sp_load(filename: *const c_char) -> c_int {
-    unsafe { SP_load(filename) }
+/// Load a Prolog source, `.po` or foreign resource file into the `user` module, like `load_files/1`.
+///
+/// # Returns
+/// [Error::Load] with the exception raised while loading, if any.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/html/sicstus.html/cpg_002dref_002dSP_005fload.html>
+pub fn sp_load(filename: &str) -> Result<(), Error> {
+    let cfilename = to_cstring(filename)?;
+    load_result(filename, unsafe { SP_load(cfilename.as_ptr()) })
 }
-pub fn sp_restore(filenmae: *const c_char) -> c_int {
-    unsafe { SP_restore(filenmae) }
+
+/// Restore a saved state, like `restore/1`. This replaces all Prolog code and data.
+///
+/// # Returns
+/// [Error::Load] with the exception raised while restoring, if any.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/html/sicstus.html/cpg_002dref_002dSP_005frestore.html>
+pub fn sp_restore(filename: &str) -> Result<(), Error> {
+    let cfilename = to_cstring(filename)?;
+    load_result(filename, unsafe { SP_restore(cfilename.as_ptr()) })
+}
+
+/// The result of [sp_load] and [sp_restore], which return SP_ERROR on an exception and SP_FAILURE on failure.
+fn load_result(file: &str, ret_val: c_int) -> Result<(), Error> {
+    if ret_val == SP_SUCCESS as c_int {
+        return Ok(());
+    }
+    let exception = if ret_val == SP_ERROR {
+        PrologException::take_pending()
+    } else {
+        None
+    };
+    Err(Error::Load {
+        file: String::from(file),
+        exception,
+    })
 }
 /// Create a Prolog stream that will call user defined functions to perform stream operations.
 ///
@@ -946,6 +1114,15 @@ pub fn sp_new_term_ref() -> SP_term_ref {
     unsafe { SP_new_term_ref() }
 }
 
+/// Reclaim *term* and all term refs created after it.
+/// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Creating%20and%20Manipulating%20SP_term_refs>
+///
+/// # Safety
+/// None of the reclaimed term refs may be used afterwards. Use [crate::TermScope] to keep the term refs of a result.
+pub unsafe fn sp_reset_term_refs(term: SP_term_ref) {
+    SP_reset_term_refs(term)
+}
+
 /// Unifies two terms.
 ///
 /// # Arguments
@@ -958,12 +1135,12 @@ pub fn sp_new_term_ref() -> SP_term_ref {
 /// Bear in mind that the unification may unblock some goals. such goals are not run in the
 /// scope of SP_unify; they remain pending until the next Prolog goal is run.
 /// See also: <https://sicstus.sics.se/sicstus/docs/latest4/pdf/sicstus.pdf#Unifying%20and%20Comparing%20Terms>
-pub fn sp_unify(x: SP_term_ref, y: SP_term_ref) -> Result<(), PrologError> {
+pub fn sp_unify(x: SP_term_ref, y: SP_term_ref) -> Result<(), Error> {
     let res = unsafe { SP_unify(x, y) };
     if res == 1 {
         Ok(())
     } else {
-        Err(PrologError::UncussefulUnificationError(x, y))
+        Err(Error::UnificationFailed)
     }
 }
 
@@ -1012,146 +1189,166 @@ pub fn sp_is_variable(term: SP_term_ref) -> bool {
 }
 
 /// Determines the type of the value of term.
-pub fn sp_term_type(term: SP_term_ref) -> Result<c_int, PrologError> {
+pub fn sp_term_type(term: SP_term_ref) -> Result<TermType, Error> {
     let res = unsafe { SP_term_type(term) };
-    if res == SP_TYPE_ERROR as c_int {
-        Err(PrologError::TypeCheckError)
-    } else {
-        Ok(res)
+    match res as u32 {
+        SP_TYPE_VARIABLE => Ok(TermType::Variable),
+        SP_TYPE_INTEGER => Ok(TermType::Integer),
+        SP_TYPE_ATOM => Ok(TermType::Atom),
+        SP_TYPE_FLOAT => Ok(TermType::Float),
+        SP_TYPE_COMPOUND => Ok(TermType::Compound),
+        _ => Err(Error::api(
+            "SP_term_type",
+            res,
+            format!("Could not determine the type of term {:?}.", term),
+        )),
     }
 }
 
-pub fn sp_put_address(term: SP_term_ref, address: *mut c_void) -> Result<(), PrologError> {
+pub fn sp_put_address(term: SP_term_ref, address: *mut c_void) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_address(term, address) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting address {:?} into term {:?}",
-            address, term
-        )))
+        Err(Error::api(
+            "SP_put_address",
+            ret_val,
+            format!("Failed putting address {:?} into term {:?}", address, term),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_list(term: SP_term_ref) -> Result<(), PrologError> {
+pub fn sp_put_list(term: SP_term_ref) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_list(term) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting list into term {:?}",
-            term
-        )))
+        Err(Error::api(
+            "SP_put_list",
+            ret_val,
+            format!("Failed putting list into term {:?}", term),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_list_codes(
-    term: SP_term_ref,
-    tail: SP_term_ref,
-    s: *const c_char,
-) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_put_list_codes(term, tail, s) };
+pub fn sp_put_list_codes(term: SP_term_ref, tail: SP_term_ref, s: &str) -> Result<(), Error> {
+    let cstring = to_cstring(s)?;
+    let ret_val = unsafe { SP_put_list_codes(term, tail, cstring.as_ptr()) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
-            s, tail, term
-        )))
+        Err(Error::api(
+            "SP_put_list_codes",
+            ret_val,
+            format!(
+                "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
+                s, tail, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_list_n_bytes(
-    term: SP_term_ref,
-    tail: SP_term_ref,
-    n: usize,
-    s: *const u8,
-) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_put_list_n_bytes(term, tail, n, s) };
+pub fn sp_put_list_n_bytes(term: SP_term_ref, tail: SP_term_ref, s: &[u8]) -> Result<(), Error> {
+    let ret_val = unsafe { SP_put_list_n_bytes(term, tail, s.len(), s.as_ptr()) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
-            s, tail, term
-        )))
+        Err(Error::api(
+            "SP_put_list_n_bytes",
+            ret_val,
+            format!(
+                "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
+                s, tail, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_list_n_codes(
-    term: SP_term_ref,
-    tail: SP_term_ref,
-    n: usize,
-    s: *const c_char,
-) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_put_list_n_codes(term, tail, n, s) };
+/// Put the character codes of `s` in front of `tail`.
+/// The string does not need to be null terminated, since its length is passed along.
+pub fn sp_put_list_n_codes(term: SP_term_ref, tail: SP_term_ref, s: &str) -> Result<(), Error> {
+    let ret_val = unsafe { SP_put_list_n_codes(term, tail, s.len(), s.as_ptr() as *const c_char) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
-            s, tail, term
-        )))
+        Err(Error::api(
+            "SP_put_list_n_codes",
+            ret_val,
+            format!(
+                "Failed putting list codes for string {:?} with tail {:?} into term {:?}",
+                s, tail, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_number_codes(term: SP_term_ref, s: *const c_char) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_put_number_codes(term, s) };
+pub fn sp_put_number_codes(term: SP_term_ref, s: &str) -> Result<(), Error> {
+    let cstring = to_cstring(s)?;
+    let ret_val = unsafe { SP_put_number_codes(term, cstring.as_ptr()) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting number codes for string {:?} into term {:?}",
-            s, term
-        )))
+        Err(Error::api(
+            "SP_put_number_codes",
+            ret_val,
+            format!(
+                "Failed putting number codes for string {:?} into term {:?}",
+                s, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_string(term: SP_term_ref, s: *const c_char) -> Result<(), PrologError> {
-    let ret_val = unsafe { SP_put_string(term, s) };
+pub fn sp_put_string(term: SP_term_ref, s: &str) -> Result<(), Error> {
+    let cstring = to_cstring(s)?;
+    let ret_val = unsafe { SP_put_string(term, cstring.as_ptr()) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting string {:?} into term {:?}",
-            s, term
-        )))
+        Err(Error::api(
+            "SP_put_string",
+            ret_val,
+            format!("Failed putting string {:?} into term {:?}", s, term),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_atom(term: SP_term_ref, atom: SP_atom) -> Result<(), PrologError> {
+pub fn sp_put_atom(term: SP_term_ref, atom: SP_atom) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_atom(term, atom) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting atom {} into term {:?}",
-            atom, term
-        )))
+        Err(Error::api(
+            "SP_put_atom",
+            ret_val,
+            format!("Failed putting atom {} into term {:?}", atom, term),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Make the term a variable.
-pub fn sp_put_variable(term: SP_term_ref) -> Result<(), PrologError> {
+pub fn sp_put_variable(term: SP_term_ref) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_variable(term) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting variable into term {:?}",
-            term
-        )))
+        Err(Error::api(
+            "SP_put_variable",
+            ret_val,
+            format!("Failed putting variable into term {:?}", term),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Make the term a float.
-pub fn sp_put_float(term: SP_term_ref, f: f64) -> Result<(), PrologError> {
+pub fn sp_put_float(term: SP_term_ref, f: f64) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_float(term, f) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting float {} into term {:?}",
-            f, term
-        )))
+        Err(Error::api(
+            "SP_put_float",
+            ret_val,
+            format!("Failed putting float {} into term {:?}", f, term),
+        ))
     } else {
         Ok(())
     }
@@ -1160,57 +1357,68 @@ pub fn sp_put_float(term: SP_term_ref, f: f64) -> Result<(), PrologError> {
 /// Assigns to *term* a reference to a compund term with all the arguments unbound variables.
 /// If arity is 0, assigns the Prolog atom whose canonical representation is *name*. This is similar
 /// calling functor/3 with the first argument unbound and the second and third arguments bound to an atom and an iteger, respectively.
-pub fn sp_put_functor(term: SP_term_ref, name: SP_atom, arity: usize) -> Result<(), PrologError> {
+pub fn sp_put_functor(term: SP_term_ref, name: SP_atom, arity: usize) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_functor(term, name, arity as c_int) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting functor {} with arity {} into term {:?}",
-            name, arity, term
-        )))
+        Err(Error::api(
+            "SP_put_functor",
+            ret_val,
+            format!(
+                "Failed putting functor {} with arity {} into term {:?}",
+                name, arity, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Make the term a float.
-pub fn sp_put_integer(term: SP_term_ref, i: i64) -> Result<(), PrologError> {
+pub fn sp_put_integer(term: SP_term_ref, i: i64) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_integer(term, i) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting integer {} into term {:?}",
-            i, term
-        )))
+        Err(Error::api(
+            "SP_put_integer",
+            ret_val,
+            format!("Failed putting integer {} into term {:?}", i, term),
+        ))
     } else {
         Ok(())
     }
 }
 
-pub fn sp_put_integer_bytes(
-    term: SP_term_ref,
-    buf: *mut c_void,
-    buf_size: usize,
-    native: bool,
-) -> Result<(), PrologError> {
+/// Save wrapper around the unsafe [SP_put_integer_bytes] function from Prolog.
+/// Puts the integer with the two's complement representation `bytes` into the term.
+/// The bytes are in native byte order if `native` is true, in which case there must be 1, 2, 4, 8 or 16 of them.
+/// Otherwise the least significant byte comes first and there may be any number of them.
+pub fn sp_put_integer_bytes(term: SP_term_ref, bytes: &[u8], native: bool) -> Result<(), Error> {
     let native = if native { 1 } else { 0 };
-    let ret_val = unsafe { SP_put_integer_bytes(term, buf, buf_size, native) };
+    // SICStus only reads from the buffer.
+    let buf = bytes.as_ptr() as *mut c_void;
+    let ret_val = unsafe { SP_put_integer_bytes(term, buf, bytes.len(), native) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting integer bytes from buffer {:?} into term {:?}",
-            buf, term
-        )))
+        Err(Error::api(
+            "SP_put_integer_bytes",
+            ret_val,
+            format!(
+                "Failed putting integer bytes {:?} into term {:?}",
+                bytes, term
+            ),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Copy a Prolog term into another term.
-pub fn sp_put_term(to: SP_term_ref, from: SP_term_ref) -> Result<(), PrologError> {
+pub fn sp_put_term(to: SP_term_ref, from: SP_term_ref) -> Result<(), Error> {
     let ret_val = unsafe { SP_put_term(to, from) };
     if ret_val == 0 {
-        Err(PrologError::TermConversionError(format!(
-            "Failed putting term {:?} into term {:?}",
-            from, to
-        )))
+        Err(Error::api(
+            "SP_put_term",
+            ret_val,
+            format!("Failed putting term {:?} into term {:?}", from, to),
+        ))
     } else {
         Ok(())
     }
@@ -1222,21 +1430,37 @@ pub fn sp_string_from_atom(atom: SP_atom) -> String {
     unsafe { string_from_ref(s) }
 }
 
+/// The text of an atom without copying it.
+///
+/// # Safety
+/// The atom must be valid and stay alive for the lifetime `'a`, e.g. because it is registered.
+pub unsafe fn sp_string_from_atom_cstr<'a>(atom: SP_atom) -> &'a CStr {
+    CStr::from_ptr(SP_string_from_atom(atom))
+}
+
 /// Registers the atom *atom* with the Prolog memory manager by incrementing its reference count.
-pub fn sp_register_atom(atom: SP_atom) -> Result<(), PrologError> {
+pub fn sp_register_atom(atom: SP_atom) -> Result<(), Error> {
     let ret_val = unsafe { SP_register_atom(atom) };
     if ret_val == 0 {
-        Err(PrologError::AtomRegistrationError(atom))
+        Err(Error::api(
+            "SP_register_atom",
+            ret_val,
+            format!("Could not register atom {}.", atom),
+        ))
     } else {
         Ok(())
     }
 }
 
 /// Unregisters the atom *atom* with the Prolog memory manager by decrementing its reference count.
-pub fn sp_unregister_atom(atom: SP_atom) -> Result<(), PrologError> {
+pub fn sp_unregister_atom(atom: SP_atom) -> Result<(), Error> {
     let ret_val = unsafe { SP_unregister_atom(atom) };
     if ret_val == 0 {
-        Err(PrologError::AtomUnregistrationError(atom))
+        Err(Error::api(
+            "SP_unregister_atom",
+            ret_val,
+            format!("Could not unregister atom {}.", atom),
+        ))
     } else {
         Ok(())
     }
@@ -1244,28 +1468,20 @@ pub fn sp_unregister_atom(atom: SP_atom) -> Result<(), PrologError> {
 
 /// If Ok, returns a pointer to the predicate definition. Otherwise an appropriate error.
 /// Slower than sp_pred
-pub fn sp_predicate(
-    name: &str,
-    arity: usize,
-    module: Option<&str>,
-) -> Result<SP_pred_ref, PrologError> {
-    let name_cstring = CString::new(name).unwrap();
-    if module.is_some() {
-        let module_cstring = CString::new(module.unwrap()).unwrap();
-        let result =
-            unsafe { SP_predicate(name_cstring.as_ptr(), arity as i64, module_cstring.as_ptr()) };
-        if result.is_null() {
-            Err(PrologError::PredicateNotFound)
-        } else {
-            Ok(result)
-        }
+pub fn sp_predicate(name: &str, arity: usize, module: Option<&str>) -> Result<SP_pred_ref, Error> {
+    let name_cstring = to_cstring(name)?;
+    let module_cstring = module.map(to_cstring).transpose()?;
+    let module_ptr = match &module_cstring {
+        Some(module_cstring) => module_cstring.as_ptr(),
+        None => core::ptr::null(),
+    };
+    let result = unsafe { SP_predicate(name_cstring.as_ptr(), arity as i64, module_ptr) };
+    if result.is_null() {
+        Err(Error::PredicateNotFound(match module {
+            Some(module) => format!("{}:{}/{}", module, name, arity),
+            None => format!("{}/{}", name, arity),
+        }))
     } else {
-        let result =
-            unsafe { SP_predicate(name_cstring.as_ptr(), arity as i64, core::ptr::null()) };
-        if result.is_null() {
-            Err(PrologError::PredicateNotFound)
-        } else {
-            Ok(result)
-        }
+        Ok(result)
     }
 }
@@ -0,0 +1,255 @@
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec::Vec};
+use core::ffi::c_char;
+
+use crate::{
+    convert::{codes_term, functor_of, functor_term, list_term, FromTerm, ToTerm},
+    error::Error,
+    sys,
+    util::string_from_ref,
+    Predicate, TermRef, TermScope,
+};
+
+/// An owned Prolog term.
+///
+/// Unlike a [TermRef], which is only a handle into the Prolog heap, a Term lives on the Rust heap.
+/// It can be kept past the foreign call that created it, compared and sent to other threads.
+///
+/// Converting between the two is lossless, including shared variables: `f(X, X)` becomes
+/// `Compound { name: "f", args: [Var(0), Var(0)] }` and back.
+/// Both directions use an explicit stack, so deeply nested terms like long lists do not overflow the call stack.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Atom(String),
+    Integer(i64),
+    /// An integer that does not fit into an i64, in decimal notation.
+    BigInt(String),
+    Float(f64),
+    /// Text that is put into Prolog as a code list.
+    /// Code lists read from Prolog are [Term::List]s of integers, since SICStus has no separate string type.
+    String(String),
+    /// A variable, identified by a number that is unique within the term.
+    Var(usize),
+    Compound {
+        name: String,
+        args: Vec<Term>,
+    },
+    /// A proper list. The empty list `[]` is `List(vec![])`.
+    List(Vec<Term>),
+    /// A list whose tail is not `[]`, e.g. `[a, b | T]`.
+    PartialList(Vec<Term>, Box<Term>),
+}
+
+impl Term {
+    /// Put the term on the Prolog heap.
+    /// Variables with the same id are the same variable in the result.
+    pub fn to_term_ref<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        enum Task<'a> {
+            Visit(&'a Term),
+            Compound(&'a str, usize),
+            List(usize),
+            PartialList(usize),
+        }
+
+        let mut variables: BTreeMap<usize, TermRef> = BTreeMap::new();
+        let mut tasks = Vec::from([Task::Visit(self)]);
+        let mut values: Vec<TermRef<'s>> = Vec::new();
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Visit(term) => match term {
+                    Term::Atom(name) => values.push(functor_term(scope, name, &[])?),
+                    Term::Integer(integer) => values.push(TermRef::new_integer(scope, *integer)),
+                    Term::BigInt(digits) => values.push(bigint_term(scope, digits)?),
+                    Term::Float(float) => values.push(TermRef::new_float(scope, *float)),
+                    Term::String(text) => values.push(codes_term(scope, text)?),
+                    Term::Var(id) => {
+                        let variable = variables
+                            .entry(*id)
+                            .or_insert_with(|| TermRef::new_variable(scope));
+                        values.push(variable.clone());
+                    }
+                    Term::Compound { name, args } => {
+                        tasks.push(Task::Compound(name, args.len()));
+                        tasks.extend(args.iter().rev().map(Task::Visit));
+                    }
+                    Term::List(items) => {
+                        tasks.push(Task::List(items.len()));
+                        tasks.extend(items.iter().rev().map(Task::Visit));
+                    }
+                    Term::PartialList(items, tail) => {
+                        tasks.push(Task::PartialList(items.len()));
+                        tasks.push(Task::Visit(tail));
+                        tasks.extend(items.iter().rev().map(Task::Visit));
+                    }
+                },
+                Task::Compound(name, arity) => {
+                    let args = values.split_off(values.len() - arity);
+                    values.push(functor_term(scope, name, &args)?);
+                }
+                Task::List(len) => {
+                    let items = values.split_off(values.len() - len);
+                    values.push(list_term(scope, items)?);
+                }
+                Task::PartialList(len) => {
+                    let list = values.pop().expect("the tail was visited");
+                    let items = values.split_off(values.len() - len);
+                    for item in items.into_iter().rev() {
+                        sys::sp_cons_list(list.term_ref(), item.term_ref(), list.term_ref())?;
+                    }
+                    values.push(list);
+                }
+            }
+        }
+        Ok(values.pop().expect("the term was visited"))
+    }
+}
+
+impl TermRef<'_> {
+    /// Copy the term into an owned [Term].
+    /// Variables are numbered from 0 in the order they first occur.
+    /// The term refs this needs are reclaimed before it returns.
+    pub fn to_term(&self) -> Result<Term, Error> {
+        TermScope::run(|scope| copy_term(&self.in_scope(scope)))
+    }
+}
+
+/// Copy `root` into an owned [Term], see [TermRef::to_term].
+fn copy_term(root: &TermRef) -> Result<Term, Error> {
+    enum Task<'s> {
+        Visit(TermRef<'s>),
+        Compound(String, usize),
+        List(usize),
+        PartialList(usize),
+    }
+
+    // Looked up when the first variable is found, so terms without variables do not run a query.
+    let mut variables: Option<Variables> = None;
+    let mut tasks = Vec::from([Task::Visit(root.clone())]);
+    let mut values: Vec<Term> = Vec::new();
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Visit(term) => {
+                if term.is_variable() {
+                    let variables = match &mut variables {
+                        Some(variables) => variables,
+                        None => variables.insert(Variables::of(root)?),
+                    };
+                    values.push(Term::Var(variables.id(&term)?));
+                } else if term.is_integer() {
+                    values.push(match term.get_integer() {
+                        Ok(integer) => Term::Integer(integer),
+                        Err(_) => Term::BigInt(number_text(&term)?),
+                    });
+                } else if term.is_float() {
+                    values.push(Term::Float(term.get_float()?));
+                } else if term.is_atom() {
+                    let (name, _) = functor_of(&term)?;
+                    values.push(if name == "[]" {
+                        Term::List(Vec::new())
+                    } else {
+                        Term::Atom(name)
+                    });
+                } else if term.get_list().is_some() {
+                    let mut items = Vec::new();
+                    let mut rest = term;
+                    while let Some((head, tail)) = rest.get_list() {
+                        items.push(head);
+                        rest = tail;
+                    }
+                    let nil = rest.is_atom() && functor_of(&rest)?.0 == "[]";
+                    if nil {
+                        tasks.push(Task::List(items.len()));
+                    } else {
+                        tasks.push(Task::PartialList(items.len()));
+                        tasks.push(Task::Visit(rest));
+                    }
+                    tasks.extend(items.into_iter().rev().map(Task::Visit));
+                } else if term.is_compound() {
+                    let (name, arity) = functor_of(&term)?;
+                    tasks.push(Task::Compound(name, arity));
+                    for i in (1..=arity).rev() {
+                        tasks.push(Task::Visit(term.get_arg(i)?));
+                    }
+                } else {
+                    return Err(Error::Conversion(format!(
+                        "term {:?} can not be converted",
+                        term
+                    )));
+                }
+            }
+            Task::Compound(name, arity) => {
+                let args = values.split_off(values.len() - arity);
+                values.push(Term::Compound { name, args });
+            }
+            Task::List(len) => {
+                let items = values.split_off(values.len() - len);
+                values.push(Term::List(items));
+            }
+            Task::PartialList(len) => {
+                let tail = values.pop().expect("the tail was visited");
+                let items = values.split_off(values.len() - len);
+                values.push(Term::PartialList(items, Box::new(tail)));
+            }
+        }
+    }
+    Ok(values.pop().expect("the term was visited"))
+}
+
+/// The variables of a term, numbered in the order `term_variables/2` lists them, which is the order of first occurrence.
+struct Variables<'s> {
+    /// The variables with their numbers, sorted by the standard order of terms for binary search.
+    sorted: Vec<(TermRef<'s>, usize)>,
+}
+
+impl<'s> Variables<'s> {
+    fn of(term: &TermRef<'s>) -> Result<Self, Error> {
+        let list = TermRef::new_variable(term.scope());
+        Predicate::new("prolog", "term_variables", 2)?.call_once(&[term.clone(), list.clone()])?;
+        let mut sorted = list
+            .list_iter()
+            .enumerate()
+            .map(|(id, variable)| variable.map(|variable| (variable, id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(Variables { sorted })
+    }
+
+    fn id(&self, variable: &TermRef) -> Result<usize, Error> {
+        self.sorted
+            .binary_search_by(|(v, _)| v.cmp(variable))
+            .map(|i| self.sorted[i].1)
+            .map_err(|_| Error::Conversion(String::from("a variable of the term was not found")))
+    }
+}
+
+fn bigint_term<'s>(scope: TermScope<'s>, digits: &str) -> Result<TermRef<'s>, Error> {
+    let term = TermRef::new(scope);
+    sys::sp_put_number_codes(term.term_ref(), digits)?;
+    Ok(term)
+}
+
+/// The text of a number, for integers that do not fit into an i64.
+fn number_text(term: &TermRef) -> Result<String, Error> {
+    let mut s: *const c_char = core::ptr::null();
+    let ret_val = sys::sp_get_number_codes(term.term_ref(), &mut s);
+    if ret_val == 0 || s.is_null() {
+        return Err(Error::api(
+            "SP_get_number_codes",
+            ret_val,
+            format!("Could not retrieve term {:?} as number codes.", term),
+        ));
+    }
+    // SP_get_number_codes returned a valid null terminated string.
+    Ok(unsafe { string_from_ref(s) })
+}
+
+impl ToTerm for Term {
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        self.to_term_ref(scope)
+    }
+}
+
+impl<'s> FromTerm<'s> for Term {
+    fn from_term(term: &TermRef<'s>) -> Result<Self, Error> {
+        term.to_term()
+    }
+}
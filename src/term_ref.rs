@@ -1,152 +1,174 @@
-use core::{cmp::Ordering, ffi::c_void};
+use core::{cmp::Ordering, ffi::c_void, fmt};
 
-use alloc::string::String;
+use alloc::{format, string::String, vec::Vec};
 use sicstus_sys::{SP_atom, SP_term_ref};
 
 use crate::{
-    sys::{self, sp_cons_list, sp_get_list, sp_new_term_ref, PrologError},
-    Atom,
+    convert::type_error,
+    error::Error,
+    sys::{self, sp_get_list},
+    Atom, TermScope,
 };
 
-#[derive(Debug)]
-pub struct TermRef {
+/// A handle to a term on the Prolog heap, valid while its [TermScope] runs.
+pub struct TermRef<'s> {
     term_ref: SP_term_ref,
+    scope: TermScope<'s>,
 }
 
-impl TermRef {
-    pub fn new() -> Self {
-        TermRef {
-            term_ref: sys::sp_new_term_ref(),
-        }
+impl<'s> TermRef<'s> {
+    pub fn new(scope: TermScope<'s>) -> Self {
+        scope.adopt(sys::sp_new_term_ref())
     }
 
-    /// Create a prolog term from a string containing prolog code.
-    pub fn from_prolog(_prolog_code: &str) -> Result<Self, PrologError> {
-        unimplemented!("TermRef::from_prolog");
+    /// Create a new term ref holding a fresh unbound variable.
+    pub fn new_variable(scope: TermScope<'s>) -> Self {
+        let mut term_ref = TermRef::new(scope);
+        term_ref.put_variable().unwrap();
+        term_ref
     }
 
-    pub fn new_integer(integer: i64) -> Self {
-        let mut term_ref = TermRef::new();
+    pub fn new_integer(scope: TermScope<'s>, integer: i64) -> Self {
+        let mut term_ref = TermRef::new(scope);
         term_ref.put_integer(integer).unwrap();
         term_ref
     }
 
-    pub fn new_float(float: f64) -> Self {
-        let mut term_ref = TermRef::new();
+    pub fn new_float(scope: TermScope<'s>, float: f64) -> Self {
+        let mut term_ref = TermRef::new(scope);
         term_ref.put_float(float).unwrap();
         term_ref
     }
 
-    pub fn new_list() -> Self {
-        let mut term_ref = TermRef::new();
+    pub fn new_list(scope: TermScope<'s>) -> Self {
+        let mut term_ref = TermRef::new(scope);
         term_ref.put_list().unwrap();
         term_ref
     }
 
+    /// Wrap a raw term ref, e.g. one passed to a foreign function.
+    ///
+    /// # Safety
+    /// `term_ref` must be a term ref that SICStus created in the current foreign call or query,
+    /// and it must stay valid while `scope` runs, i.e. no scope that ends earlier may reclaim it.
+    pub unsafe fn from_raw(scope: TermScope<'s>, term_ref: SP_term_ref) -> Self {
+        TermRef { term_ref, scope }
+    }
+
     pub fn term_ref(&self) -> SP_term_ref {
         self.term_ref
     }
 
-    pub fn unify(&self, other: &Self) -> Result<(), PrologError> {
+    /// The scope the term ref belongs to. Term refs created from this one, e.g. by [TermRef::get_arg], belong to it
+    /// as well.
+    pub fn scope(&self) -> TermScope<'s> {
+        self.scope
+    }
+
+    /// The same term in a scope that ends before the scope of this term ref, typically the one of
+    /// [TermScope::run]. The term refs created from the result belong to that scope and are reclaimed with it.
+    pub fn in_scope<'t>(&self, scope: TermScope<'t>) -> TermRef<'t>
+    where
+        's: 't,
+    {
+        // The term ref stays valid until 's ends, which is after 't.
+        unsafe { TermRef::from_raw(scope, self.term_ref) }
+    }
+
+    pub fn unify(&self, other: &TermRef<'_>) -> Result<(), Error> {
         sys::sp_unify(self.term_ref, other.term_ref)
     }
 
-    pub fn cmp(&self, other: &Self) -> Ordering {
+    pub fn cmp(&self, other: &TermRef<'_>) -> Ordering {
         sys::sp_compare(self.term_ref, other.term_ref)
     }
 }
 
 // Assigning to the SP_term_ref
-impl TermRef {
-    pub fn put_address(&mut self, address: *mut core::ffi::c_void) -> Result<(), PrologError> {
+impl TermRef<'_> {
+    pub fn put_address(&mut self, address: *mut core::ffi::c_void) -> Result<(), Error> {
         sys::sp_put_address(self.term_ref, address)
     }
 
-    pub fn put_atom(&mut self, atom: SP_atom) -> Result<(), PrologError> {
+    pub fn put_atom(&mut self, atom: SP_atom) -> Result<(), Error> {
         sys::sp_put_atom(self.term_ref, atom)
     }
 
-    pub fn put_float(&mut self, float: f64) -> Result<(), PrologError> {
+    pub fn put_float(&mut self, float: f64) -> Result<(), Error> {
         sys::sp_put_float(self.term_ref, float)
     }
 
-    pub fn put_functor(&mut self, atom: SP_atom, arity: usize) -> Result<(), PrologError> {
+    pub fn put_functor(&mut self, atom: SP_atom, arity: usize) -> Result<(), Error> {
         sys::sp_put_functor(self.term_ref, atom, arity)
     }
 
-    pub fn put_integer(&mut self, integer: i64) -> Result<(), PrologError> {
+    pub fn put_integer(&mut self, integer: i64) -> Result<(), Error> {
         sys::sp_put_integer(self.term_ref, integer)
     }
 
-    pub fn put_integer_bytes(&mut self, bytes: &mut [u8], native: bool) -> Result<(), PrologError> {
-        sys::sp_put_integer_bytes(
-            self.term_ref,
-            bytes.as_ptr() as *mut c_void,
-            bytes.len(),
-            native,
-        )
+    /// Put an integer of any size, given as two's complement bytes. See [sys::sp_put_integer_bytes].
+    pub fn put_integer_bytes(&mut self, bytes: &[u8], native: bool) -> Result<(), Error> {
+        sys::sp_put_integer_bytes(self.term_ref, bytes, native)
     }
 
-    pub fn put_list(&mut self) -> Result<(), PrologError> {
+    pub fn put_list(&mut self) -> Result<(), Error> {
         sys::sp_put_list(self.term_ref)
     }
 
-    pub fn put_list_codes(&mut self, tail: &TermRef, s: &str) -> Result<(), PrologError> {
-        sys::sp_put_list_codes(self.term_ref, tail.term_ref(), s.as_ptr() as *const i8)
+    pub fn put_list_codes(&mut self, tail: &TermRef<'_>, s: &str) -> Result<(), Error> {
+        sys::sp_put_list_codes(self.term_ref, tail.term_ref(), s)
     }
 
+    /// Put the first `n` bytes of `bytes` as a list of byte values in front of `tail`.
     pub fn put_list_n_bytes(
         &mut self,
-        tail: &TermRef,
+        tail: &TermRef<'_>,
         n: usize,
         bytes: &[u8],
-    ) -> Result<(), PrologError> {
-        sys::sp_put_list_n_bytes(
-            self.term_ref,
-            tail.term_ref(),
-            n,
-            bytes.as_ptr() as *const u8,
-        )
+    ) -> Result<(), Error> {
+        let bytes = bytes.get(..n).ok_or_else(|| {
+            Error::Conversion(format!(
+                "{} bytes requested from a slice of {}",
+                n,
+                bytes.len()
+            ))
+        })?;
+        sys::sp_put_list_n_bytes(self.term_ref, tail.term_ref(), bytes)
     }
 
-    pub fn put_list_n_codes(
-        &mut self,
-        tail: &TermRef,
-        n: usize,
-        s: &str,
-    ) -> Result<(), PrologError> {
-        sys::sp_put_list_n_codes(
-            self.term_ref,
-            tail.term_ref(),
-            n,
-            s as *const str as *const i8,
-        )
+    /// Put the characters of the first `n` bytes of `s` as a code list in front of `tail`.
+    /// `n` must lie on a character boundary.
+    pub fn put_list_n_codes(&mut self, tail: &TermRef<'_>, n: usize, s: &str) -> Result<(), Error> {
+        let s = s.get(..n).ok_or_else(|| {
+            Error::Conversion(format!("byte {} is not a character boundary of {:?}", n, s))
+        })?;
+        sys::sp_put_list_n_codes(self.term_ref, tail.term_ref(), s)
     }
 
-    pub fn put_number_codes(&mut self, s: &str) -> Result<(), PrologError> {
-        sys::sp_put_number_codes(self.term_ref, s as *const str as *const i8)
+    pub fn put_number_codes(&mut self, s: &str) -> Result<(), Error> {
+        sys::sp_put_number_codes(self.term_ref, s)
     }
 
-    pub fn put_string(&mut self, s: &str) -> Result<(), PrologError> {
-        sys::sp_put_string(self.term_ref, s as *const str as *const i8)
+    pub fn put_string(&mut self, s: &str) -> Result<(), Error> {
+        sys::sp_put_string(self.term_ref, s)
     }
 
-    pub fn put_term(&mut self, term: &TermRef) -> Result<(), PrologError> {
+    pub fn put_term(&mut self, term: &TermRef<'_>) -> Result<(), Error> {
         sys::sp_put_term(self.term_ref, term.term_ref())
     }
 
-    pub fn put_variable(&mut self) -> Result<(), PrologError> {
+    pub fn put_variable(&mut self) -> Result<(), Error> {
         sys::sp_put_variable(self.term_ref)
     }
 
-    pub fn cons(&mut self, head: TermRef) -> Result<(), PrologError> {
+    pub fn cons(&mut self, head: TermRef<'_>) -> Result<(), Error> {
         assert!(self.is_list());
         sys::sp_cons_list(self.term_ref, head.term_ref, self.term_ref)
     }
 }
 
 // Type Checking the SP_term_ref
-impl TermRef {
+impl TermRef<'_> {
     pub fn is_atom(&self) -> bool {
         sys::sp_is_atom(self.term_ref)
     }
@@ -181,138 +203,119 @@ impl TermRef {
 }
 
 // Extracting from the SP_term_ref
-impl TermRef {
-    pub fn get_address(&self) -> Result<*mut c_void, PrologError> {
+impl<'s> TermRef<'s> {
+    pub fn get_address(&self) -> Result<*mut c_void, Error> {
         sys::sp_get_address(self.term_ref)
     }
 
-    pub fn get_arg(&self, index: usize) -> Result<TermRef, PrologError> {
+    /// The argument at `index` of a compound term, counting from 1 like `arg/3`.
+    ///
+    /// Returns a type error if the term is not compound and [Error::ArgumentOutOfRange] if `index` is not in `1..=arity`.
+    pub fn get_arg(&self, index: usize) -> Result<TermRef<'s>, Error> {
+        if !self.is_compound() {
+            return Err(type_error("compound", self));
+        }
+        let (_, arity) = sys::sp_get_functor(self.term_ref)?;
+        if index == 0 || index > arity {
+            return Err(Error::ArgumentOutOfRange { index, arity });
+        }
         let term_ref: SP_term_ref = sys::sp_get_arg(index, self.term_ref)?;
-        Ok(term_ref.into())
+        Ok(self.scope.adopt(term_ref))
     }
 
-    pub fn get_atom(&self) -> Result<Atom, PrologError> {
+    pub fn get_atom(&self) -> Result<Atom, Error> {
         sys::sp_get_atom(self.term_ref).map(|atom_id| Atom::from(atom_id))
     }
 
-    pub fn get_float(&self) -> Result<f64, PrologError> {
+    pub fn get_float(&self) -> Result<f64, Error> {
         sys::sp_get_float(self.term_ref)
     }
 
-    pub fn get_functor(&self) -> Result<(Atom, usize), PrologError> {
+    pub fn get_functor(&self) -> Result<(Atom, usize), Error> {
         let (atom, arity) = sys::sp_get_functor(self.term_ref)?;
         Ok((Atom::from(atom), arity))
     }
 
-    pub fn get_integer(&self) -> Result<i64, PrologError> {
+    pub fn get_integer(&self) -> Result<i64, Error> {
         sys::sp_get_integer(self.term_ref)
     }
 
-    pub fn get_list(&self) -> Option<(TermRef, TermRef)> {
+    /// The two's complement bytes of an integer of any size, least significant byte first.
+    pub fn get_integer_bytes(&self) -> Result<Vec<u8>, Error> {
+        sys::sp_get_integer_bytes(self.term_ref)
+    }
+
+    pub fn get_list(&self) -> Option<(TermRef<'s>, TermRef<'s>)> {
         let (head, tail) = sys::sp_get_list(self.term_ref)?;
-        Some((head.into(), tail.into()))
+        Some((self.scope.adopt(head), self.scope.adopt(tail)))
     }
 
-    pub fn get_list_codes(&self) -> Result<String, PrologError> {
+    pub fn get_list_codes(&self) -> Result<String, Error> {
         sys::sp_get_list_codes(self.term_ref)
     }
 
-    pub fn get_string(&self) -> Result<String, PrologError> {
+    pub fn get_string(&self) -> Result<String, Error> {
         sys::sp_get_string(self.term_ref)
     }
 }
 
-impl From<SP_term_ref> for TermRef {
-    fn from(term_ref: SP_term_ref) -> Self {
-        TermRef { term_ref }
-    }
-}
-
-impl Into<SP_term_ref> for TermRef {
+impl Into<SP_term_ref> for TermRef<'_> {
     fn into(self) -> SP_term_ref {
         self.term_ref
     }
 }
 
-impl IntoIterator for TermRef {
-    type Item = TermRef;
-    type IntoIter = TermRefIterator;
+impl<'s> IntoIterator for TermRef<'s> {
+    type Item = TermRef<'s>;
+    type IntoIter = TermRefIterator<'s>;
 
     fn into_iter(self) -> Self::IntoIter {
         TermRefIterator::new(self)
     }
 }
 
-pub struct TermRefIterator {
+/// Iterates over the elements of a list, stopping at the first tail that is not a list cell.
+/// Use [TermRef::list_iter] to find out whether the list was proper.
+pub struct TermRefIterator<'s> {
     term_ref: SP_term_ref,
+    scope: TermScope<'s>,
 }
 
-impl TermRefIterator {
-    pub fn new(term_ref: TermRef) -> Self {
+impl<'s> TermRefIterator<'s> {
+    pub fn new(term_ref: TermRef<'s>) -> Self {
         assert!(
             term_ref.is_list(),
             "cannot cast term_ref into iterator. Not a list."
         );
         TermRefIterator {
             term_ref: term_ref.term_ref(),
+            scope: term_ref.scope(),
         }
     }
 }
 
-impl Iterator for TermRefIterator {
-    type Item = TermRef;
+impl<'s> Iterator for TermRefIterator<'s> {
+    type Item = TermRef<'s>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let (head, tail) = sp_get_list(self.term_ref)?;
         self.term_ref = tail;
-        Some(head.into())
+        Some(self.scope.adopt(head))
     }
 }
 
-impl Clone for TermRef {
+impl Clone for TermRef<'_> {
     fn clone(&self) -> Self {
-        let mut term_ref = TermRef::new();
+        let mut term_ref = TermRef::new(self.scope);
         term_ref.put_term(self).unwrap();
         term_ref
     }
 }
 
-impl FromIterator<TermRef> for TermRef {
-    fn from_iter<I: IntoIterator<Item = TermRef>>(iter: I) -> Self {
-        let l = sp_new_term_ref();
-        for item in iter {
-            sp_cons_list(l, item.term_ref, l).unwrap();
-        }
-        l.into()
-    }
-}
-
-impl From<u32> for TermRef {
-    fn from(integer: u32) -> Self {
-        TermRef::new_integer(integer as i64)
-    }
-}
-
-impl From<u64> for TermRef {
-    fn from(integer: u64) -> Self {
-        TermRef::new_integer(integer as i64)
-    }
-}
-
-impl From<u8> for TermRef {
-    fn from(integer: u8) -> Self {
-        TermRef::new_integer(integer as i64)
-    }
-}
-
-impl From<usize> for TermRef {
-    fn from(integer: usize) -> Self {
-        TermRef::new_integer(integer as i64)
-    }
-}
-
-impl From<f64> for TermRef {
-    fn from(float: f64) -> Self {
-        TermRef::new_float(float)
+impl fmt::Debug for TermRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TermRef")
+            .field("term_ref", &self.term_ref)
+            .finish()
     }
 }
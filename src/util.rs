@@ -1,24 +1,22 @@
-use core::ffi::c_char;
+use core::ffi::{c_char, CStr};
 
-use alloc::string::String;
+use alloc::{ffi::CString, string::String};
+
+use crate::error::Error;
 
 /// Create a new [String] from a *const pointer to a C string.
+/// SICStus encodes text as UTF-8. Invalid sequences are replaced with U+FFFD.
 /// This does not take ownership of the pointer. The caller is responsible for freeing the memory.
 /// # Safety
 /// The pointer must be valid and point to a null terminated C string.
 pub unsafe fn string_from_ref(sp: *const c_char) -> String {
-    let mut result = String::new();
-    let mut cp: *const c_char = sp;
-    loop {
-        let c: c_char = *cp;
-        let c = c as u8 as char;
-        if c == '\0' {
-            break;
-        }
-        result.push(c as u8 as char);
-        cp = cp.add(1);
-    }
-    result
+    String::from_utf8_lossy(CStr::from_ptr(sp).to_bytes()).into_owned()
+}
+
+/// Create a null terminated copy of `s` to pass to SICStus.
+/// Fails if `s` contains a null byte, since SICStus would silently cut the text off there.
+pub fn to_cstring(s: &str) -> Result<CString, Error> {
+    CString::new(s).map_err(Error::InteriorNul)
 }
 
 #[cfg(test)]
@@ -36,6 +34,47 @@ fn test_string_copy() {
     assert_eq!(copied_string, "Hello, World!".to_string());
 }
 
+/// Convert a Rust type name like `HttpRequest` to a Prolog atom name like `http_request`.
+#[cfg(feature = "serde")]
+pub fn snake_case(name: &str) -> String {
+    let chars: alloc::vec::Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
+
+#[cfg(all(test, feature = "serde"))]
+#[test]
+fn test_snake_case() {
+    assert_eq!(snake_case("Point"), "point");
+    assert_eq!(snake_case("HttpRequest"), "http_request");
+    assert_eq!(snake_case("HTTPRequest"), "http_request");
+    assert_eq!(snake_case("Vec3D"), "vec3_d");
+    assert_eq!(snake_case("already_snake"), "already_snake");
+}
+
+#[cfg(test)]
+#[test]
+fn test_string_from_ref_utf8() {
+    for text in ["_ÖALKSDFJÖLK-", "grüße", "🦀 rust"] {
+        let s = to_cstring(text).unwrap();
+        assert_eq!(unsafe { string_from_ref(s.as_ptr()) }, text);
+    }
+    assert!(matches!(to_cstring("a\0b"), Err(Error::InteriorNul(_))));
+}
+
 // #[allow(dead_code)]
 // pub fn is_valid_atom_name(name: &str) -> bool {
 //     // TODO Check for correct bracket pairs
@@ -0,0 +1,134 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::{
+    convert::{check_functor, functor_of, FromTerm},
+    error::Error,
+    sys, Atom, TermRef,
+};
+
+/// The type of a term, as determined by `SP_term_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TermType {
+    Variable,
+    Integer,
+    Atom,
+    Float,
+    Compound,
+}
+
+/// A term taken apart for pattern matching, see [TermRef::view].
+///
+/// ```ignore
+/// match term.view()? {
+///     TermView::Integer(i) => ...,
+///     TermView::Compound { name, arity: 2, args } if name.as_str() == "point" => ...,
+///     _ => return Err(type_error("point", &term)),
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub enum TermView<'s> {
+    Var,
+    Atom(Atom),
+    Integer(i64),
+    /// An integer that does not fit into an i64.
+    /// Convert the term into an `i128` or, with the `num-bigint` feature, a `BigInt` to get its value.
+    BigInteger,
+    Float(f64),
+    Compound {
+        name: Atom,
+        arity: usize,
+        args: Args<'s>,
+    },
+}
+
+impl<'s> TermRef<'s> {
+    pub fn term_type(&self) -> Result<TermType, Error> {
+        sys::sp_term_type(self.term_ref())
+    }
+
+    /// Take the term apart by its type.
+    /// This reads the type once instead of going through the `is_*` functions one by one.
+    pub fn view(&self) -> Result<TermView<'s>, Error> {
+        Ok(match self.term_type()? {
+            TermType::Variable => TermView::Var,
+            TermType::Atom => TermView::Atom(self.get_atom()?),
+            // SP_get_integer fails exactly for the integers that do not fit.
+            TermType::Integer => match self.get_integer() {
+                Ok(integer) => TermView::Integer(integer),
+                Err(_) => TermView::BigInteger,
+            },
+            TermType::Float => TermView::Float(self.get_float()?),
+            TermType::Compound => {
+                let (name, arity) = self.get_functor()?;
+                TermView::Compound {
+                    name,
+                    arity,
+                    args: Args::new(self, arity),
+                }
+            }
+        })
+    }
+
+    /// Iterate over the arguments of a compound term. An atom has no arguments.
+    pub fn args(&self) -> Result<Args<'s>, Error> {
+        let (_, arity) = functor_of(self)?;
+        Ok(Args::new(self, arity))
+    }
+
+    /// Check that the term is `name(...)` with `arity` arguments and return the arguments.
+    ///
+    /// ```ignore
+    /// let [x, y] = term.expect_functor("point", 2)?.try_into().unwrap();
+    /// ```
+    pub fn expect_functor(&self, name: &str, arity: usize) -> Result<Vec<TermRef<'s>>, Error> {
+        check_functor(self, name, arity)?;
+        Ok(Args::new(self, arity).collect())
+    }
+
+    /// The argument at `index`, counting from 1, converted with [FromTerm].
+    pub fn arg<T: FromTerm<'s>>(&self, index: usize) -> Result<T, Error> {
+        T::from_term(&self.get_arg(index)?)
+    }
+}
+
+/// The arguments of a compound term, in order.
+#[derive(Debug, Clone)]
+pub struct Args<'s> {
+    term: TermRef<'s>,
+    next: usize,
+    arity: usize,
+}
+
+impl<'s> Args<'s> {
+    fn new(term: &TermRef<'s>, arity: usize) -> Self {
+        Args {
+            term: term.clone(),
+            next: 1,
+            arity,
+        }
+    }
+}
+
+impl<'s> Iterator for Args<'s> {
+    type Item = TermRef<'s>;
+
+    fn next(&mut self) -> Option<TermRef<'s>> {
+        if self.next > self.arity {
+            return None;
+        }
+        // The index is within the arity, so the bounds check of get_arg is not needed.
+        let arg = sys::sp_get_arg(self.next, self.term.term_ref()).ok()?;
+        self.next += 1;
+        Some(self.term.scope().adopt(arg))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.arity + 1 - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Args<'_> {}
+
+impl FusedIterator for Args<'_> {}
@@ -0,0 +1,142 @@
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    convert::{functor_term, list_term, text_from_term, ToTerm},
+    error::Error,
+    sys, Predicate, TermRef, TermScope,
+};
+
+/// Set once library(codesio), which provides `with_output_to_codes/2`, is loaded.
+static CODESIO_LOADED: AtomicBool = AtomicBool::new(false);
+
+/// Options for [TermRef::to_string_with_options], corresponding to the options of `write_term/2`.
+///
+/// ```ignore
+/// let text = term.to_string_with_options(&WriteOptions::new().quoted(true).max_depth(5))?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    quoted: bool,
+    ignore_ops: bool,
+    numbervars: bool,
+    max_depth: Option<usize>,
+}
+
+impl WriteOptions {
+    pub fn new() -> Self {
+        WriteOptions::default()
+    }
+
+    /// Quote atoms and strings where necessary, so the text can be read back.
+    pub fn quoted(mut self, quoted: bool) -> Self {
+        self.quoted = quoted;
+        self
+    }
+
+    /// Write operators in functional notation, e.g. `+(1,2)` instead of `1+2`.
+    pub fn ignore_ops(mut self, ignore_ops: bool) -> Self {
+        self.ignore_ops = ignore_ops;
+        self
+    }
+
+    /// Write `'$VAR'(N)` terms as variable names.
+    pub fn numbervars(mut self, numbervars: bool) -> Self {
+        self.numbervars = numbervars;
+        self
+    }
+
+    /// Write deeper subterms as `...`.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    fn to_term<'s>(&self, scope: TermScope<'s>) -> Result<TermRef<'s>, Error> {
+        let option =
+            |name: &str, value: &dyn ToTerm| functor_term(scope, name, &[value.to_term(scope)?]);
+        let mut options = Vec::from([
+            option("quoted", &self.quoted)?,
+            option("ignore_ops", &self.ignore_ops)?,
+            option("numbervars", &self.numbervars)?,
+        ]);
+        if let Some(max_depth) = self.max_depth {
+            options.push(option("max_depth", &max_depth)?);
+        }
+        list_term(scope, options)
+    }
+}
+
+impl TermRef<'_> {
+    /// The text `write_canonical/1` writes for the term.
+    pub fn to_string_canonical(&self) -> Result<String, Error> {
+        self.write_with("write_canonical", None)
+    }
+
+    /// The text `writeq/1` writes for the term.
+    pub fn to_string_quoted(&self) -> Result<String, Error> {
+        self.write_with("writeq", None)
+    }
+
+    /// The text `write_term/2` writes for the term with the given options.
+    pub fn to_string_with_options(&self, options: &WriteOptions) -> Result<String, Error> {
+        self.write_with("write_term", Some(options))
+    }
+
+    /// Call the write predicate `name` with the term and the options, if any, and collect its output.
+    /// The term refs this needs are reclaimed before it returns.
+    fn write_with(&self, name: &str, options: Option<&WriteOptions>) -> Result<String, Error> {
+        load_codesio()?;
+        TermScope::run(|scope| {
+            let mut goal_args = Vec::from([self.in_scope(scope)]);
+            if let Some(options) = options {
+                goal_args.push(options.to_term(scope)?);
+            }
+            let goal = functor_term(scope, name, &goal_args)?;
+            let with_output_to_codes = Predicate::new("codesio", "with_output_to_codes", 2)?;
+            match with_output_to_codes.call(&[goal, TermRef::new_variable(scope)])? {
+                Some(args) => text_from_term(&args[1]),
+                None => Err(Error::Conversion(String::from(
+                    "the term could not be written",
+                ))),
+            }
+        })
+    }
+}
+
+/// Load library(codesio) unless it was loaded already.
+pub(crate) fn load_codesio() -> Result<(), Error> {
+    if CODESIO_LOADED.load(Ordering::Acquire) {
+        return Ok(());
+    }
+    let use_module = Predicate::new("prolog", "use_module", 1)?;
+    TermScope::run(|scope| {
+        let library = functor_term(scope, "library", &[functor_term(scope, "codesio", &[])?])?;
+        use_module.call_for_side_effects(&[library])
+    })?;
+    CODESIO_LOADED.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Forget that library(codesio) was loaded, since the runtime was stopped or its code replaced.
+pub(crate) fn forget_codesio() {
+    CODESIO_LOADED.store(false, Ordering::Release);
+}
+
+/// Writes the term like `print/1`, which uses `portray/1` hooks and quotes where necessary.
+///
+/// Writing runs a Prolog query. If that is not possible, e.g. because the runtime is not running,
+/// `<term ref N>` is written instead, so formatting a term never fails.
+impl fmt::Display for TermRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if sys::sp_is_initialized() {
+            if let Ok(text) = self.write_with("print", None) {
+                return f.write_str(&text);
+            }
+        }
+        write!(f, "<term ref {}>", self.term_ref())
+    }
+}